@@ -0,0 +1,115 @@
+#ifndef PAGAT_H
+#define PAGAT_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Result of every fallible call; anything other than `PAGAT_ERROR_OK` means the call had no effect.
+ */
+typedef enum PagatError {
+  PAGAT_ERROR_OK = 0,
+  PAGAT_ERROR_NULL_POINTER,
+  PAGAT_ERROR_INVALID_UTF8,
+  PAGAT_ERROR_OUT_OF_BOUNDS,
+  PAGAT_ERROR_NO_ENDPOINT_FOR_EDGE,
+  PAGAT_ERROR_NO_WEIGHT_FOR_NODE,
+  PAGAT_ERROR_NO_WEIGHT_FOR_EDGE,
+  PAGAT_ERROR_UNSATISFIABLE_CONSTRAINTS,
+  PAGAT_ERROR_INTERIOR_NUL,
+  PAGAT_ERROR_PANIC,
+  PAGAT_ERROR_NEEDS_GROUP,
+} PagatError;
+
+/**
+ * Opaque handle to the settlement returned by `pagat_payments_who_pays_whom`.
+ */
+typedef struct PagatObligations PagatObligations;
+
+/**
+ * Opaque handle to the payments recorded by a group.
+ */
+typedef struct PagatPayments PagatPayments;
+
+/**
+ * A single transfer: `from` pays `amount` cents to `to`.
+ */
+typedef struct PagatObligation {
+  const char *from;
+  const char *to;
+  int32_t amount;
+} PagatObligation;
+
+/**
+ * Returns a static, NUL-terminated description of `error`.
+ */
+const char *pagat_error_message(enum PagatError error);
+
+/**
+ * Creates an empty set of payments, to be released with `pagat_payments_free`.
+ */
+struct PagatPayments *pagat_payments_new(void);
+
+/**
+ * Releases payments created by `pagat_payments_new`; passing NULL is a no-op.
+ *
+ * # Safety
+ * `payments` must be NULL or a handle from `pagat_payments_new` that was not freed yet.
+ */
+void pagat_payments_free(struct PagatPayments *payments);
+
+/**
+ * Records that `from` paid `amount` cents for the `to_len` people in `to`.
+ *
+ * # Safety
+ * `payments` must be a live handle, `from` a NUL-terminated string and `to` an array of `to_len`
+ * NUL-terminated strings (it may be NULL when `to_len` is 0).
+ */
+enum PagatError pagat_payments_record(struct PagatPayments *payments,
+                                      const char *from,
+                                      int32_t amount,
+                                      const char *const *to,
+                                      size_t to_len);
+
+/**
+ * Solves who pays whom, storing a new handle in `out` on success, to be released with
+ * `pagat_obligations_free`.
+ *
+ * # Safety
+ * `payments` must be a live handle and `out` a valid pointer to write to.
+ */
+enum PagatError pagat_payments_who_pays_whom(const struct PagatPayments *payments,
+                                             struct PagatObligations **out);
+
+/**
+ * Number of transfers in the settlement, 0 for NULL.
+ *
+ * # Safety
+ * `obligations` must be NULL or a live handle.
+ */
+size_t pagat_obligations_len(const struct PagatObligations *obligations);
+
+/**
+ * Reads the transfer at `index` into `out`; its strings are borrowed from `obligations`.
+ *
+ * # Safety
+ * `obligations` must be a live handle and `out` a valid pointer to write to.
+ */
+enum PagatError pagat_obligations_get(const struct PagatObligations *obligations,
+                                      size_t index,
+                                      struct PagatObligation *out);
+
+/**
+ * Releases a settlement returned by `pagat_payments_who_pays_whom`; passing NULL is a no-op.
+ *
+ * # Safety
+ * `obligations` must be NULL or a handle that was not freed yet.
+ */
+void pagat_obligations_free(struct PagatObligations *obligations);
+
+#endif  /* PAGAT_H */
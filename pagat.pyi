@@ -0,0 +1,50 @@
+from typing import List, Tuple
+
+class SolverError(Exception): ...
+
+class Person:
+    def __init__(self, name: str) -> None: ...
+    @property
+    def name(self) -> str: ...
+
+class Money:
+    """An amount in cents, such that `Money(100)` is $1.00."""
+
+    def __init__(self, cents: int) -> None: ...
+    @staticmethod
+    def parse(amount: str) -> Money: ...
+    @property
+    def cents(self) -> int: ...
+    def __int__(self) -> int: ...
+
+class Payment:
+    def __init__(self, from_: Person, amount: Money, to: List[Person]) -> None: ...
+    @property
+    def from_(self) -> Person: ...
+    @property
+    def amount(self) -> Money: ...
+    @property
+    def to(self) -> List[Person]: ...
+
+class Payments:
+    def __init__(self, payments: List[Payment] = ...) -> None: ...
+    def record(self, payment: Payment) -> None: ...
+    def balances(self) -> List[Tuple[Person, Money]]: ...
+    def who_pays_whom(self) -> Obligations:
+        """Raises `SolverError` if the payments cannot be settled."""
+    def __len__(self) -> int: ...
+
+class Obligation:
+    @property
+    def from_(self) -> Person: ...
+    @property
+    def to(self) -> Person: ...
+    @property
+    def amount(self) -> Money: ...
+
+class Obligations:
+    def __len__(self) -> int: ...
+    def __getitem__(self, index: int) -> Obligation: ...
+    def to_list(self) -> List[Obligation]: ...
+    def to_records(self) -> List[Tuple[str, str, int]]:
+        """`(from, to, cents)` tuples, ready for `pandas.DataFrame.from_records`."""
@@ -0,0 +1,330 @@
+use clap::Parser;
+use serde_json::{json, Value};
+use std::{
+    error::Error,
+    fs, io,
+    path::{Path, PathBuf},
+};
+use tiny_http::{Header, Method, Response, Server};
+
+use pagat::{Group, GroupError, Payment};
+
+/// Serve groups, payments, balances and settlements over HTTP/JSON.
+///
+/// Routes:
+///   GET    /groups
+///   GET    /groups/{group}
+///   PUT    /groups/{group}                   create or replace a group
+///   DELETE /groups/{group}
+///   GET    /groups/{group}/payments
+///   POST   /groups/{group}/payments          record a payment
+///   PUT    /groups/{group}/payments/{index}  replace a payment
+///   DELETE /groups/{group}/payments/{index}
+///   GET    /groups/{group}/balances
+///   GET    /groups/{group}/settlement        who pays whom
+#[derive(Parser)]
+#[command(name = "pagat-server", version, verbatim_doc_comment)]
+struct Cli {
+    /// Address to listen on.
+    #[arg(short, long, default_value = "127.0.0.1:8080")]
+    listen: String,
+
+    /// Directory where every group is stored as a JSON file.
+    #[arg(short, long, default_value = "groups")]
+    data: PathBuf,
+}
+
+fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let cli = Cli::parse();
+    fs::create_dir_all(&cli.data)?;
+
+    let server = Server::http(&cli.listen)?;
+    eprintln!("listening on http://{}", server.server_addr());
+
+    serve(&server, &Store(cli.data));
+
+    Ok(())
+}
+
+/// Handles requests one at a time, so concurrent writes to a group file can't interleave.
+fn serve(server: &Server, store: &Store) {
+    let content_type =
+        Header::from_bytes("Content-Type", "application/json").expect("valid header");
+
+    for mut request in server.incoming_requests() {
+        let mut body = String::new();
+
+        let (status, value) = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => route(store, request.method(), request.url(), &body),
+            Err(err) => (400, json!({ "error": err.to_string() })),
+        };
+
+        // A 204 response must not have a body.
+        let response = match status {
+            204 => Response::from_string(String::new()).with_status_code(status),
+            _ => Response::from_string(value.to_string())
+                .with_status_code(status)
+                .with_header(content_type.clone()),
+        };
+
+        if let Err(err) = request.respond(response) {
+            eprintln!("could not respond: {err}");
+        }
+    }
+}
+
+/// Stores each group as `{name}.json` inside a directory.
+struct Store(PathBuf);
+
+impl Store {
+    fn path(&self, name: &str) -> PathBuf {
+        self.0.join(format!("{name}.json"))
+    }
+
+    fn names(&self) -> io::Result<Vec<String>> {
+        let mut names = fs::read_dir(&self.0)?
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                (path.extension()? == "json").then_some(())?;
+                Some(path.file_stem()?.to_str()?.to_owned())
+            })
+            .collect::<Vec<_>>();
+
+        names.sort();
+        Ok(names)
+    }
+
+    fn load(&self, name: &str) -> Result<Option<Group>, GroupError> {
+        let path = self.path(name);
+
+        if !Path::exists(&path) {
+            return Ok(None);
+        }
+
+        Group::load(path).map(Some)
+    }
+
+    fn save(&self, name: &str, group: &Group) -> Result<(), GroupError> {
+        group.save(self.path(name))
+    }
+}
+
+type Reply = (u16, Value);
+
+fn error(status: u16, message: impl ToString) -> Reply {
+    (status, json!({ "error": message.to_string() }))
+}
+
+fn route(store: &Store, method: &Method, url: &str, body: &str) -> Reply {
+    let path = url.split('?').next().unwrap_or_default();
+    let segments = path
+        .trim_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+
+    match segments.as_slice() {
+        ["groups"] if method == &Method::Get => match store.names() {
+            Ok(names) => (200, json!(names)),
+            Err(err) => error(500, err),
+        },
+        ["groups", name, rest @ ..] => {
+            if !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            {
+                return error(400, "group names may only contain letters, digits, - and _");
+            }
+
+            match store.load(name) {
+                Ok(group) => route_group(store, name, group, method, rest, body),
+                Err(err) => error(500, err),
+            }
+        }
+        _ => error(404, format!("no route for {method} {path}")),
+    }
+}
+
+fn route_group(
+    store: &Store,
+    name: &str,
+    group: Option<Group>,
+    method: &Method,
+    rest: &[&str],
+    body: &str,
+) -> Reply {
+    if let ([], Method::Put) = (rest, method) {
+        let group = match body.trim() {
+            "" => Group::default(),
+            body => match Group::from_json(body) {
+                Ok(group) => group,
+                Err(err) => return error(400, err),
+            },
+        };
+
+        return match store.save(name, &group) {
+            Ok(()) => (200, json!(group)),
+            Err(err) => error(500, err),
+        };
+    }
+
+    let Some(mut group) = group else {
+        return error(404, format!("no group named {name}"));
+    };
+
+    let payment = || serde_json::from_str::<Payment>(body);
+    let saved = |group: &Group, status: u16, value: Value| match store.save(name, group) {
+        Ok(()) => (status, value),
+        Err(err) => error(500, err),
+    };
+
+    match (rest, method) {
+        ([], Method::Get) => (200, json!(group)),
+        ([], Method::Delete) => match fs::remove_file(store.path(name)) {
+            Ok(()) => (204, Value::Null),
+            Err(err) => error(500, err),
+        },
+        (["payments"], Method::Get) => (200, json!(group.payments())),
+        (["payments"], Method::Post) => match payment() {
+            Ok(payment) => {
+                group.record(payment);
+                let index = group.payments().raw().len() - 1;
+                saved(&group, 201, json!({ "index": index }))
+            }
+            Err(err) => error(400, err),
+        },
+        (["payments", index], Method::Put | Method::Delete) => {
+            let Ok(index) = index.parse::<usize>() else {
+                return error(400, format!("invalid payment index {index}"));
+            };
+
+            let previous = match method {
+                Method::Put => match payment() {
+                    Ok(payment) => group.replace(index, payment),
+                    Err(err) => return error(400, err),
+                },
+                _ => group.remove(index),
+            };
+
+            match previous {
+                Some(previous) => saved(&group, 200, json!(previous)),
+                None => error(404, format!("no payment at index {index}")),
+            }
+        }
+        (["balances"], Method::Get) => {
+            let balances = group
+                .balances()
+                .into_iter()
+                .map(|(person, balance)| json!({ "person": person, "balance": balance }))
+                .collect::<Vec<_>>();
+
+            (200, json!(balances))
+        }
+        (["settlement"], Method::Get) => match group.who_pays_whom() {
+            Ok(obligations) => (200, json!(obligations)),
+            Err(err) => error(500, err),
+        },
+        _ => error(405, format!("{method} is not supported here")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{Read, Write},
+        net::TcpStream,
+        sync::Arc,
+        thread,
+    };
+
+    fn request(addr: &str, method: &str, path: &str, body: &str) -> (u16, Value) {
+        let (status, body) = raw_request(addr, method, path, body);
+
+        (status, serde_json::from_str(&body).unwrap_or(Value::Null))
+    }
+
+    fn raw_request(addr: &str, method: &str, path: &str, body: &str) -> (u16, String) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: {addr}\r\nConnection: close\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+
+        let status = response[9..12].parse().unwrap();
+        let (_, body) = response.split_once("\r\n\r\n").unwrap();
+
+        (status, body.to_owned())
+    }
+
+    #[test]
+    fn test_server_on_localhost() {
+        let data = std::env::temp_dir().join(format!("pagat-server-{}", std::process::id()));
+        fs::create_dir_all(&data).unwrap();
+
+        let server = Arc::new(Server::http("127.0.0.1:0").unwrap());
+        let addr = server.server_addr().to_ip().unwrap().to_string();
+
+        let handle = {
+            let server = Arc::clone(&server);
+            let store = Store(data.clone());
+            thread::spawn(move || serve(&server, &store))
+        };
+
+        assert_eq!(200, request(&addr, "PUT", "/groups/trip", "").0);
+        assert_eq!(
+            (201, json!({ "index": 0 })),
+            request(
+                &addr,
+                "POST",
+                "/groups/trip/payments",
+                r#"{"from": "A", "amount": 3000, "to": ["A", "B", "C"]}"#,
+            )
+        );
+        assert_eq!((200, json!(["trip"])), request(&addr, "GET", "/groups", ""));
+        assert_eq!(
+            (
+                200,
+                json!([
+                    { "person": "A", "balance": 2000 },
+                    { "person": "B", "balance": -1000 },
+                    { "person": "C", "balance": -1000 },
+                ])
+            ),
+            request(&addr, "GET", "/groups/trip/balances", "")
+        );
+        assert_eq!(
+            (
+                200,
+                json!([
+                    { "from": "B", "to": "A", "amount": 1000 },
+                    { "from": "C", "to": "A", "amount": 1000 },
+                ])
+            ),
+            request(&addr, "GET", "/groups/trip/settlement", "")
+        );
+        assert_eq!(
+            404,
+            request(&addr, "DELETE", "/groups/trip/payments/7", "").0
+        );
+        assert_eq!(
+            400,
+            request(&addr, "GET", "/groups/..%2Fetc/balances", "").0
+        );
+        assert_eq!(404, request(&addr, "GET", "/groups/nope/settlement", "").0);
+        assert_eq!(
+            (204, String::new()),
+            raw_request(&addr, "DELETE", "/groups/trip", "")
+        );
+        assert_eq!(404, request(&addr, "GET", "/groups/trip", "").0);
+
+        server.unblock();
+        handle.join().unwrap();
+        fs::remove_dir_all(data).unwrap();
+    }
+}
@@ -0,0 +1,511 @@
+use clap::Parser;
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::Line,
+    widgets::{Block, Clear, List, ListItem, Paragraph, Row, Table, TableState},
+    DefaultTerminal, Frame,
+};
+use std::{error::Error, path::PathBuf};
+
+use pagat::{Group, Money, Payment, Person, SolverEvent};
+
+/// Split the bill at the table: browse payments, balances and the live settlement of a group file.
+#[derive(Parser)]
+#[command(name = "pagat-tui", version, about)]
+struct Cli {
+    /// Group file to read and write.
+    #[arg(short, long, default_value = "pagat.json")]
+    file: PathBuf,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    let mut app = App::new(cli.file.clone(), Group::load(&cli.file)?);
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+
+    Ok(result?)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum View {
+    Overview,
+    Solver,
+}
+
+/// The add/edit payment popup; `to` is a comma separated list of people.
+/// An edited payment keeps everything the form doesn't show.
+#[derive(Debug, Default)]
+struct Form {
+    editing: Option<(usize, Payment)>,
+    fields: [String; 3],
+    focus: usize,
+}
+
+impl Form {
+    const LABELS: [&'static str; 3] =
+        ["From", "Amount", "To (comma separated, empty for everyone)"];
+
+    fn edit(index: usize, payment: &Payment) -> Self {
+        let to = payment
+            .to()
+            .iter()
+            .map(Person::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Self {
+            editing: Some((index, payment.clone())),
+            fields: [payment.from().to_string(), payment.amount().to_string(), to],
+            focus: 0,
+        }
+    }
+
+    fn payment(&self, everyone: &[Person]) -> Result<Payment, String> {
+        let from = self.fields[0].trim();
+        if from.is_empty() {
+            return Err("who paid?".to_owned());
+        }
+
+        let amount = self.fields[1]
+            .parse::<Money>()
+            .map_err(|err| err.to_string())?;
+
+        let mut to = self.fields[2]
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(Person::from)
+            .collect::<Vec<_>>();
+
+        let original = self.editing.as_ref().map(|(_, payment)| payment);
+        if to.is_empty() {
+            to = match original {
+                Some(payment) if payment.is_to_members() => payment.to().to_vec(),
+                _ => everyone.to_vec(),
+            };
+        }
+
+        let builder = original.map_or_else(Payment::builder, Payment::to_builder);
+        Ok(builder
+            .from(Person::new(from))
+            .amount(amount)
+            .to(&to)
+            .build())
+    }
+}
+
+struct App {
+    file: PathBuf,
+    group: Group,
+    payments: TableState,
+    view: View,
+    form: Option<Form>,
+    status: String,
+    quit: bool,
+}
+
+impl App {
+    fn new(file: PathBuf, group: Group) -> Self {
+        Self {
+            file,
+            group,
+            payments: TableState::default().with_selected(Some(0)),
+            view: View::Overview,
+            form: None,
+            status: String::new(),
+            quit: false,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> std::io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.on_key(key);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn selected(&self) -> Option<usize> {
+        self.payments
+            .selected()
+            .filter(|i| *i < self.group.payments().raw().len())
+    }
+
+    fn on_key(&mut self, key: KeyEvent) {
+        if self.form.is_some() {
+            self.on_form_key(key);
+            return;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Down | KeyCode::Char('j') => self.payments.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.payments.select_previous(),
+            KeyCode::Char('a') => self.form = Some(Form::default()),
+            KeyCode::Char('e') | KeyCode::Enter => {
+                if let Some(i) = self.selected() {
+                    self.form = Some(Form::edit(i, &self.group.payments().raw()[i]));
+                }
+            }
+            KeyCode::Char('d') | KeyCode::Delete => {
+                if let Some(i) = self.selected() {
+                    self.group.remove(i);
+                    self.save("payment removed");
+                }
+            }
+            KeyCode::Char('s') | KeyCode::Tab => {
+                self.view = match self.view {
+                    View::Overview => View::Solver,
+                    View::Solver => View::Overview,
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn on_form_key(&mut self, key: KeyEvent) {
+        let Some(form) = self.form.as_mut() else {
+            return;
+        };
+
+        match key.code {
+            KeyCode::Esc => self.form = None,
+            KeyCode::Tab | KeyCode::Down => form.focus = (form.focus + 1) % form.fields.len(),
+            KeyCode::BackTab | KeyCode::Up => {
+                form.focus = (form.focus + form.fields.len() - 1) % form.fields.len()
+            }
+            KeyCode::Backspace => {
+                form.fields[form.focus].pop();
+            }
+            KeyCode::Char(c) => form.fields[form.focus].push(c),
+            KeyCode::Enter => match form.payment(self.group.people()) {
+                Ok(payment) => {
+                    let editing = form.editing.as_ref().map(|(i, _)| *i);
+                    self.form = None;
+
+                    match editing {
+                        Some(i) => {
+                            self.group.replace(i, payment);
+                            self.save("payment updated");
+                        }
+                        None => {
+                            self.group.record(payment);
+                            self.payments
+                                .select(Some(self.group.payments().raw().len() - 1));
+                            self.save("payment added");
+                        }
+                    }
+                }
+                Err(err) => self.status = err,
+            },
+            _ => {}
+        }
+    }
+
+    fn save(&mut self, done: &str) {
+        self.status = match self.group.save(&self.file) {
+            Ok(()) => done.to_owned(),
+            Err(err) => err.to_string(),
+        };
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .areas(main);
+
+        self.draw_payments(frame, left);
+
+        match self.view {
+            View::Overview => {
+                let [balances, settlement] =
+                    Layout::vertical([Constraint::Percentage(50), Constraint::Percentage(50)])
+                        .areas(right);
+
+                self.draw_balances(frame, balances);
+                self.draw_settlement(frame, settlement);
+            }
+            View::Solver => self.draw_solver(frame, right),
+        }
+
+        let help = "a add  e edit  d delete  s solver view  q quit";
+        let line = if self.status.is_empty() {
+            Line::from(help).dark_gray()
+        } else {
+            Line::from(format!("{}  |  {help}", self.status))
+        };
+        frame.render_widget(line, status);
+
+        if let Some(form) = &self.form {
+            draw_form(frame, form);
+        }
+    }
+
+    fn draw_payments(&mut self, frame: &mut Frame, area: Rect) {
+        let rows = self.group.payments().raw().iter().map(|payment| {
+            let to = payment
+                .to()
+                .iter()
+                .map(Person::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            Row::new([payment.from().to_string(), payment.amount().to_string(), to])
+        });
+
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(25),
+                Constraint::Length(10),
+                Constraint::Fill(1),
+            ],
+        )
+        .header(Row::new(["From", "Amount", "To"]).bold())
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+        .block(Block::bordered().title(" Payments "));
+
+        frame.render_stateful_widget(table, area, &mut self.payments);
+    }
+
+    fn draw_balances(&self, frame: &mut Frame, area: Rect) {
+        let rows = self.group.balances().into_iter().map(|(person, balance)| {
+            let color = match balance.raw() {
+                b if b > 0 => Color::Green,
+                b if b < 0 => Color::Red,
+                _ => Color::Reset,
+            };
+
+            Row::new([person.to_string(), balance.to_string()]).fg(color)
+        });
+
+        let table = Table::new(rows, [Constraint::Fill(1), Constraint::Length(10)])
+            .header(Row::new(["Person", "Balance"]).bold())
+            .block(Block::bordered().title(" Balances "));
+
+        frame.render_widget(table, area);
+    }
+
+    fn draw_settlement(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered().title(" Settlement ");
+
+        match self.group.who_pays_whom() {
+            Ok(obligations) => {
+                let rows = obligations.raw().iter().map(|o| {
+                    Row::new([o.from.to_string(), o.to.to_string(), o.amount.to_string()])
+                });
+
+                let table = Table::new(
+                    rows,
+                    [
+                        Constraint::Fill(1),
+                        Constraint::Fill(1),
+                        Constraint::Length(10),
+                    ],
+                )
+                .header(Row::new(["From", "To", "Amount"]).bold())
+                .block(block);
+
+                frame.render_widget(table, area);
+            }
+            Err(err) => frame.render_widget(Paragraph::new(err.to_string()).block(block), area),
+        }
+    }
+
+    /// Every step the solver took, with the edges it dropped highlighted.
+    fn draw_solver(&self, frame: &mut Frame, area: Rect) {
+        let block = Block::bordered().title(" Solver ");
+
+        let trace = match self.group.who_pays_whom_traced() {
+            Ok((_, trace)) => trace,
+            Err(err) => {
+                frame.render_widget(Paragraph::new(err.to_string()).block(block), area);
+                return;
+            }
+        };
+
+        let items = trace.raw().iter().map(|event| {
+            let style = match event {
+                SolverEvent::Pass(_) => Style::new().bold(),
+                SolverEvent::Removed { .. } | SolverEvent::Zeroed { .. } => Style::new()
+                    .fg(Color::Red)
+                    .add_modifier(Modifier::CROSSED_OUT),
+                SolverEvent::Merged { .. } => Style::new().fg(Color::Cyan),
+                SolverEvent::Rerouted { .. } | SolverEvent::Reversed { .. } => {
+                    Style::new().fg(Color::Yellow)
+                }
+            };
+
+            ListItem::new(event.to_string()).style(style)
+        });
+
+        frame.render_widget(List::new(items).block(block), area);
+    }
+}
+
+fn draw_form(frame: &mut Frame, form: &Form) {
+    let area = centered(frame.area(), 60, 11);
+    let title = match form.editing {
+        Some(_) => " Edit payment ",
+        None => " Add payment ",
+    };
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Block::bordered()
+            .title(title)
+            .title_bottom(" enter save  esc cancel "),
+        area,
+    );
+
+    let fields = Layout::vertical([Constraint::Length(3); 3])
+        .margin(1)
+        .split(area);
+
+    for (i, (label, value)) in Form::LABELS.iter().zip(&form.fields).enumerate() {
+        let style = if i == form.focus {
+            Style::new().fg(Color::Yellow)
+        } else {
+            Style::new()
+        };
+
+        frame.render_widget(
+            Paragraph::new(value.as_str()).block(Block::bordered().title(*label).style(style)),
+            fields[i],
+        );
+    }
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::crossterm::event::KeyModifiers;
+
+    fn press(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            let code = match c {
+                '\t' => KeyCode::Tab,
+                '\n' => KeyCode::Enter,
+                c => KeyCode::Char(c),
+            };
+
+            app.on_key(KeyEvent::new(code, KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn test_add_and_edit_payment() {
+        let file = std::env::temp_dir().join(format!("pagat-tui-{}.json", std::process::id()));
+        let mut app = App::new(
+            file.clone(),
+            Group::new(&[Person::new("A"), Person::new("B")]),
+        );
+
+        press(&mut app, "aA\t20\t\n");
+        assert!(app.form.is_none());
+        assert_eq!(
+            vec![
+                (Person::new("A"), Money::new(1000)),
+                (Person::new("B"), Money::new(-1000))
+            ],
+            app.group.balances()
+        );
+
+        // Editing keeps the payer and only changes the amount.
+        press(&mut app, "e\t");
+        for _ in 0.."20.00".len() {
+            app.on_key(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+        }
+        press(&mut app, "5\n");
+        assert_eq!(Money::new(500), app.group.payments().raw()[0].amount());
+
+        let mut terminal =
+            ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, 24)).unwrap();
+        for keys in ["", "s", "e"] {
+            press(&mut app, keys);
+            terminal.draw(|frame| app.draw(frame)).unwrap();
+        }
+
+        assert_eq!(1, Group::load(&file).unwrap().payments().raw().len());
+        std::fs::remove_file(file).unwrap();
+    }
+
+    #[test]
+    fn test_edit_keeps_what_the_form_does_not_show() {
+        let file = std::env::temp_dir().join(format!("pagat-tui-edit-{}.json", std::process::id()));
+        let (a, b) = (Person::new("A"), Person::new("B"));
+        let mut group = Group::new(&[a.clone(), b.clone()]);
+        group.record(
+            Payment::builder()
+                .from(a.clone())
+                .amount(Money::new(2000))
+                .to(&[a.clone(), b.clone()])
+                .date(pagat::NaiveDate::from_ymd_opt(2024, 5, 1).unwrap())
+                .description("Cabin")
+                .category("lodging")
+                .tag("trip")
+                .cap_for(b.clone(), Money::new(500))
+                .weight(a.clone(), 2)
+                .weight(b.clone(), 1)
+                .weighted()
+                .to_members()
+                .build(),
+        );
+        let original = group.payments().raw()[0].clone();
+        let mut app = App::new(file.clone(), group);
+
+        // Saving without changes keeps the payment as it was, and so does changing its amount.
+        press(
+            &mut app, "e
+",
+        );
+        press(&mut app, "e	");
+        for _ in 0.."20.00".len() {
+            app.on_key(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+        }
+        press(
+            &mut app, "30
+",
+        );
+
+        let edited = &app.group.payments().raw()[0];
+        assert_eq!(Money::new(3000), edited.amount());
+        assert_eq!(original.from(), edited.from());
+        assert_eq!(original.to(), edited.to());
+        assert_eq!(original.id(), edited.id());
+        assert_eq!(original.date(), edited.date());
+        assert_eq!(Some("Cabin"), edited.description());
+        assert_eq!(Some("lodging"), edited.category());
+        assert_eq!(original.tags(), edited.tags());
+        assert_eq!(Some(Money::new(500)), edited.cap_of(&b));
+        assert_eq!(original.weights(), edited.weights());
+        assert!(edited.is_weighted() && edited.is_to_members());
+
+        std::fs::remove_file(file).unwrap();
+    }
+}
@@ -0,0 +1,433 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use std::{error::Error, num::NonZeroU32, path::PathBuf};
+
+use pagat::{
+    Budget, Group, Money, NaiveDate, Obligations, Payment, Period, Person, Report, SolverError,
+    Strategy,
+};
+
+/// Split the bill within a group, keeping track of it in a local file.
+#[derive(Parser)]
+#[command(name = "pagat", version, about)]
+struct Cli {
+    /// Group file to read and write.
+    #[arg(short, long, global = true, default_value = "pagat.json")]
+    file: PathBuf,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Record a payment made by someone.
+    Add {
+        /// Who paid.
+        from: Person,
+
+        /// How much was paid, e.g. 12.34.
+        amount: Money,
+
+        /// Who the payment was for, defaults to everyone in the group.
+        #[arg(short, long, value_delimiter = ',')]
+        to: Vec<Person>,
+
+        /// When the payment was made, e.g. 2024-05-17.
+        #[arg(long)]
+        date: Option<NaiveDate>,
+
+        /// What the payment was for.
+        #[arg(short, long)]
+        description: Option<String>,
+
+        /// Category of the expense, e.g. food.
+        #[arg(short, long)]
+        category: Option<String>,
+
+        /// Free-form tags, separated by commas.
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+
+        /// Split among whoever is a member of the group on the payment's date, instead of `--to`.
+        #[arg(long, conflicts_with = "to")]
+        members: bool,
+
+        /// The most anyone takes of the payment, the rest being split among the others.
+        #[arg(long)]
+        cap: Option<Money>,
+
+        /// Split in proportion to everyone's weight, see `weight`.
+        #[arg(long)]
+        weighted: bool,
+    },
+
+    /// Set how many parts of the `--weighted` payments someone takes, such as their income.
+    Weight { person: Person, weight: NonZeroU32 },
+
+    /// Set a spending limit for a category, a person, or a person in a category.
+    Budget {
+        limit: Money,
+
+        #[arg(short, long, required_unless_present = "person")]
+        category: Option<String>,
+
+        #[arg(short, long)]
+        person: Option<Person>,
+    },
+
+    /// Add someone to the group from a date on.
+    Join { person: Person, date: NaiveDate },
+
+    /// Remove someone from the group after a date.
+    Leave { person: Person, date: NaiveDate },
+
+    /// List the recorded payments.
+    List,
+
+    /// Show how much everyone is owed (positive) or owes (negative).
+    Balances,
+
+    /// Show who pays whom to settle the bill.
+    Settle {
+        /// Route every transfer through this person, who collects from debtors and pays creditors.
+        #[arg(long, conflicts_with = "out")]
+        hub: Option<Person>,
+
+        /// Only the transfers that settle this person, e.g. when they leave the group.
+        #[arg(long)]
+        out: Option<Person>,
+    },
+
+    /// Suggest who should pay the next shared expense: whoever owes the most.
+    Next {
+        /// How much the expense is expected to be, to pick whoever paying it evens out balances best.
+        amount: Option<Money>,
+    },
+
+    /// Print the settlement in a machine readable format.
+    Export {
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+
+        /// Route every transfer through this person, same as `settle --hub`.
+        #[arg(long)]
+        hub: Option<Person>,
+    },
+
+    /// Summarize the spending: per person, per category, over time and the largest expenses.
+    Report {
+        #[arg(long, value_enum, default_value_t = ReportFormat::Markdown)]
+        format: ReportFormat,
+
+        /// How spending is grouped over time.
+        #[arg(long, value_enum, default_value_t = ReportPeriod::Month)]
+        period: ReportPeriod,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportPeriod {
+    Day,
+    Week,
+    Month,
+}
+
+impl From<ReportPeriod> for Period {
+    fn from(period: ReportPeriod) -> Self {
+        match period {
+            ReportPeriod::Day => Period::Day,
+            ReportPeriod::Week => Period::Week,
+            ReportPeriod::Month => Period::Month,
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    let mut group = Group::load(&cli.file)?;
+
+    match cli.command {
+        Command::Add {
+            from,
+            amount,
+            to,
+            date,
+            description,
+            category,
+            tags,
+            members,
+            cap,
+            weighted,
+        } => {
+            let to = if to.is_empty() {
+                group.people().to_vec()
+            } else {
+                to
+            };
+            if to.is_empty() && !members {
+                return Err("the group is empty, say who the payment was for with --to".into());
+            }
+
+            let mut payment = Payment::builder().from(from).amount(amount).to(&to);
+            if let Some(date) = date {
+                payment = payment.date(date);
+            }
+            if let Some(description) = description {
+                payment = payment.description(description);
+            }
+            if let Some(category) = category {
+                payment = payment.category(category);
+            }
+            for tag in tags {
+                payment = payment.tag(tag);
+            }
+            if members {
+                payment = payment.to_members();
+            }
+            if let Some(cap) = cap {
+                payment = payment.cap(cap);
+            }
+            if weighted {
+                payment = payment.weighted();
+            }
+
+            let payment = payment.build();
+            for overrun in group.check(&payment) {
+                eprintln!("warning: {overrun}");
+            }
+
+            group.record(payment);
+            if let Some(payment) = group.resolved_payments().raw().last() {
+                let unallocated = payment.unallocated();
+                if unallocated.raw() > 0 {
+                    eprintln!(
+                        "warning: everyone is capped, {} pays {unallocated} of it alone",
+                        payment.from()
+                    );
+                }
+            }
+            if weighted {
+                for person in group.missing_weights() {
+                    eprintln!("warning: {person} has no weight, the weighted payments they share are split equally");
+                }
+            }
+            group.save(&cli.file)?;
+        }
+        Command::Weight { person, weight } => {
+            group.set_weight(person, weight);
+            group.save(&cli.file)?;
+        }
+        Command::Budget {
+            limit,
+            category,
+            person,
+        } => {
+            let budget = match (person, category) {
+                (Some(person), Some(category)) => Budget::person_in(person, category, limit),
+                (Some(person), None) => Budget::person(person, limit),
+                (None, Some(category)) => Budget::category(category, limit),
+                (None, None) => unreachable!("clap requires a category or a person"),
+            };
+
+            group.set_budget(budget);
+            group.save(&cli.file)?;
+        }
+        Command::Join { person, date } => {
+            group.join(person, date);
+            group.save(&cli.file)?;
+        }
+        Command::Leave { person, date } => {
+            group.leave(person, date);
+            group.save(&cli.file)?;
+        }
+        Command::List => {
+            let rows = group
+                .resolved_payments()
+                .raw()
+                .iter()
+                .enumerate()
+                .map(|(i, payment)| {
+                    vec![
+                        (i + 1).to_string(),
+                        payment.from().to_string(),
+                        payment.amount().to_string(),
+                        join(payment.to()),
+                        payment.date().map(|d| d.to_string()).unwrap_or_default(),
+                        payment.description().unwrap_or_default().to_owned(),
+                    ]
+                })
+                .collect::<Vec<_>>();
+
+            print!(
+                "{}",
+                table(&["#", "From", "Amount", "To", "Date", "Description"], &rows)
+            );
+        }
+        Command::Balances => {
+            let rows = group
+                .balances()
+                .iter()
+                .map(|(person, balance)| vec![person.to_string(), balance.to_string()])
+                .collect::<Vec<_>>();
+
+            print!("{}", table(&["Person", "Balance"], &rows));
+        }
+        Command::Settle { hub, out } => {
+            let obligations = match out {
+                Some(person) => group.settle_out(&person),
+                None => settle(&group, hub)?,
+            };
+
+            print!("{}", settlement_table(&obligations));
+        }
+        Command::Next { amount } => match group.who_pays_next(amount) {
+            Some(person) => println!("{person}"),
+            None => println!("Nobody is in the group"),
+        },
+        Command::Export { format, hub } => {
+            let obligations = settle(&group, hub)?;
+
+            match format {
+                Format::Json => println!("{}", serde_json::to_string_pretty(&obligations)?),
+                Format::Csv => {
+                    println!("from,to,amount");
+                    for o in obligations.raw() {
+                        println!("{},{},{}", csv(o.from.raw()), csv(o.to.raw()), o.amount);
+                    }
+                }
+            }
+        }
+        Command::Report { format, period } => {
+            let report = Report::builder()
+                .period(period.into())
+                .build(&group.resolved_payments());
+
+            match format {
+                ReportFormat::Markdown => print!("{}", report.to_markdown()),
+                ReportFormat::Html => print!("{}", report.to_html()),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Who pays whom, through `hub` when there is one.
+fn settle(group: &Group, hub: Option<Person>) -> Result<Obligations, SolverError> {
+    match hub {
+        Some(treasurer) => group
+            .resolved_payments()
+            .solver()
+            .with_strategy(Strategy::Hub(treasurer))
+            .solve(),
+        None => group.who_pays_whom(),
+    }
+}
+
+fn join(people: &[Person]) -> String {
+    people
+        .iter()
+        .map(Person::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn csv(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn settlement_table(obligations: &Obligations) -> String {
+    let rows = obligations
+        .raw()
+        .iter()
+        .map(|o| vec![o.from.to_string(), o.to.to_string(), o.amount.to_string()])
+        .collect::<Vec<_>>();
+
+    table(&["From", "To", "Amount"], &rows)
+}
+
+/// Renders rows as a plain text table, with every column as wide as its widest cell.
+fn table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let widths = headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| {
+            rows.iter()
+                .map(|row| row[i].chars().count())
+                .chain([header.chars().count()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+
+    let line = |cells: Vec<&str>| {
+        let cells = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>();
+
+        format!("{}\n", cells.join("  ").trim_end())
+    };
+
+    let separators = widths.iter().map(|w| "-".repeat(*w)).collect::<Vec<_>>();
+
+    let mut out = line(headers.to_vec());
+    out.push_str(&line(separators.iter().map(String::as_str).collect()));
+
+    for row in rows {
+        out.push_str(&line(row.iter().map(String::as_str).collect()));
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pagat::Obligation;
+
+    #[test]
+    fn test_settlement_table() {
+        let obligations = Obligations::builder()
+            .record(
+                Obligation::builder()
+                    .from(Person::new("Bob"))
+                    .to(Person::new("Alice"))
+                    .amount(Money::new(1250))
+                    .build(),
+            )
+            .record(
+                Obligation::builder()
+                    .from(Person::new("Carol"))
+                    .to(Person::new("Alice"))
+                    .amount(Money::new(300))
+                    .build(),
+            )
+            .build();
+
+        assert_eq!(
+            "From   To     Amount\n\
+             -----  -----  ------\n\
+             Bob    Alice  12.50\n\
+             Carol  Alice  3.00\n",
+            settlement_table(&obligations)
+        );
+    }
+}
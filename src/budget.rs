@@ -0,0 +1,327 @@
+use std::fmt;
+
+use crate::{Money, Payment, Payments, Person};
+
+/// A spending limit, checked with [`Budgets::check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Budget {
+    /// The total of the payments in a category, such as food for the whole trip.
+    Category { category: String, limit: Money },
+    /// What someone takes of the payments, or of the ones in a category when there is one.
+    Person {
+        person: Person,
+        category: Option<String>,
+        limit: Money,
+    },
+}
+
+impl Budget {
+    #[inline(always)]
+    pub fn category(category: impl Into<String>, limit: Money) -> Self {
+        Self::Category {
+            category: category.into(),
+            limit,
+        }
+    }
+
+    #[inline(always)]
+    pub const fn person(person: Person, limit: Money) -> Self {
+        Self::Person {
+            person,
+            category: None,
+            limit,
+        }
+    }
+
+    #[inline(always)]
+    pub fn person_in(person: Person, category: impl Into<String>, limit: Money) -> Self {
+        Self::Person {
+            person,
+            category: Some(category.into()),
+            limit,
+        }
+    }
+
+    #[inline(always)]
+    pub const fn limit(&self) -> Money {
+        match self {
+            Self::Category { limit, .. } | Self::Person { limit, .. } => *limit,
+        }
+    }
+
+    /// How much of the budget `payments` use.
+    pub fn spent(&self, payments: &Payments) -> Money {
+        let spent = payments
+            .raw()
+            .iter()
+            .map(|payment| match self {
+                Self::Category { category, .. } => match payment.category() == Some(category) {
+                    true => payment.amount().raw(),
+                    false => 0,
+                },
+                Self::Person {
+                    person, category, ..
+                } => match category.is_none() || payment.category() == category.as_deref() {
+                    true => payment
+                        .shares()
+                        .iter()
+                        .filter(|(p, _)| p == person)
+                        .map(|(_, share)| share.raw())
+                        .sum(),
+                    false => 0,
+                },
+            })
+            .sum();
+
+        Money::new(spent)
+    }
+
+    /// Whether both budgets limit the same spending, whatever their limit.
+    #[inline(always)]
+    fn limits_same(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Category { category: a, .. }, Self::Category { category: b, .. }) => a == b,
+            (
+                Self::Person {
+                    person: a,
+                    category: x,
+                    ..
+                },
+                Self::Person {
+                    person: b,
+                    category: y,
+                    ..
+                },
+            ) => a == b && x == y,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Budget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Category { category, .. } => write!(f, "{category}"),
+            Self::Person {
+                person,
+                category: None,
+                ..
+            } => write!(f, "{person}"),
+            Self::Person {
+                person,
+                category: Some(category),
+                ..
+            } => write!(f, "{person} in {category}"),
+        }
+    }
+}
+
+/// A budget that was gone over.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overrun {
+    pub budget: Budget,
+    pub spent: Money,
+}
+
+impl Overrun {
+    /// How much more than the limit was spent.
+    #[inline(always)]
+    pub fn over(&self) -> Money {
+        Money::new(self.spent.raw() - self.budget.limit().raw())
+    }
+}
+
+impl fmt::Display for Overrun {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} is {} over budget: {} of {}",
+            self.budget,
+            self.over(),
+            self.spent,
+            self.budget.limit()
+        )
+    }
+}
+
+/// The spending limits of a group, see [`crate::Group::set_budget`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Budgets(Vec<Budget>);
+
+impl Budgets {
+    #[inline(always)]
+    pub fn new(budgets: &[Budget]) -> Self {
+        Self(budgets.to_vec())
+    }
+
+    #[inline(always)]
+    pub const fn raw(&self) -> &Vec<Budget> {
+        &self.0
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Adds `budget`, replacing the one limiting the same spending if there was any.
+    #[inline(always)]
+    pub fn set(&mut self, budget: Budget) {
+        match self.0.iter_mut().find(|b| b.limits_same(&budget)) {
+            Some(existing) => *existing = budget,
+            None => self.0.push(budget),
+        }
+    }
+
+    /// Caps everyone with a [`Budget::Person`] at what is left of it, payment after payment,
+    /// so that what they can't take is split among the others instead.
+    pub fn resolve(&self, payments: &Payments) -> Payments {
+        let mut resolved = Payments::default();
+
+        for payment in payments.raw() {
+            let mut builder = payment.to_builder();
+
+            for budget in &self.0 {
+                let Budget::Person {
+                    person,
+                    category,
+                    limit,
+                } = budget
+                else {
+                    continue;
+                };
+                if !payment.participants().contains(person)
+                    || category
+                        .as_deref()
+                        .is_some_and(|c| payment.category() != Some(c))
+                {
+                    continue;
+                }
+
+                let left = Money::new((limit.raw() - budget.spent(&resolved).raw()).max(0));
+                if payment.cap_of(person).is_none_or(|cap| cap > left) {
+                    builder = builder.cap_for(person.clone(), left);
+                }
+            }
+
+            resolved.record(builder.build());
+        }
+
+        resolved
+    }
+
+    /// Every budget `payments` go over.
+    pub fn overruns(&self, payments: &Payments) -> Vec<Overrun> {
+        self.0
+            .iter()
+            .map(|budget| Overrun {
+                budget: budget.clone(),
+                spent: budget.spent(payments),
+            })
+            .filter(|overrun| overrun.spent > overrun.budget.limit())
+            .collect()
+    }
+
+    /// The budgets recording `payment` after `payments` would go over, or further over.
+    pub fn check(&self, payments: &Payments, payment: &Payment) -> Vec<Overrun> {
+        let mut after = payments.clone();
+        after.record(payment.clone());
+
+        self.overruns(&after)
+            .into_iter()
+            .filter(|overrun| overrun.spent > overrun.budget.spent(payments))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Group;
+
+    #[test]
+    fn test_budgets_and_caps() {
+        let (a, b, c) = (Person::new("A"), Person::new("B"), Person::new("C"));
+        let everyone = [a.clone(), b.clone(), c.clone()];
+
+        let mut group = Group::new(&everyone);
+        group
+            .set_budget(Budget::category("food", Money::new(10000)))
+            .set_budget(Budget::person_in(c.clone(), "lodging", Money::new(20000)));
+
+        // C takes at most 100.00 of the cabin, the other 50.00 of their part is split between A and B.
+        let cabin = Payment::builder()
+            .from(a.clone())
+            .amount(Money::new(45000))
+            .to(&everyone)
+            .category("lodging")
+            .cap_for(c.clone(), Money::new(10000))
+            .build();
+        assert_eq!(
+            vec![
+                (a.clone(), Money::new(17500)),
+                (b.clone(), Money::new(17500)),
+                (c.clone(), Money::new(10000))
+            ],
+            cabin.shares()
+        );
+        assert!(group.check(&cabin).is_empty());
+        group.record(cabin);
+
+        let dinner = Payment::builder()
+            .from(b.clone())
+            .amount(Money::new(6000))
+            .to(&everyone)
+            .category("food")
+            .build();
+        group.record(dinner.clone());
+
+        let overruns = group.check(&dinner);
+        assert_eq!(1, overruns.len());
+        assert_eq!(Money::new(2000), overruns[0].over());
+        assert_eq!(
+            "food is 20.00 over budget: 120.00 of 100.00",
+            overruns[0].to_string()
+        );
+        assert!(group.overruns().is_empty());
+
+        // C has 100.00 left of their lodging budget, so the rest of their part of the hotel is split between A and B.
+        let hotel = Payment::builder()
+            .from(b.clone())
+            .amount(Money::new(45000))
+            .to(&everyone)
+            .category("lodging")
+            .build();
+        assert!(group.check(&hotel).is_empty());
+        group.record(hotel);
+        assert_eq!(
+            vec![
+                (a.clone(), Money::new(17500)),
+                (b.clone(), Money::new(17500)),
+                (c.clone(), Money::new(10000))
+            ],
+            group.resolved_payments().raw()[2].shares()
+        );
+
+        // Nobody takes more than 100.00, and what nobody can take is on the payer.
+        let capped = Payment::builder()
+            .from(a.clone())
+            .amount(Money::new(40000))
+            .to(&everyone)
+            .cap(Money::new(10000))
+            .build();
+        assert_eq!(
+            30000,
+            capped
+                .shares()
+                .iter()
+                .map(|(_, share)| share.raw())
+                .sum::<i32>()
+        );
+        assert_eq!(Money::new(10000), capped.unallocated());
+        assert_eq!(Money::new(0), dinner.unallocated());
+    }
+}
@@ -0,0 +1,457 @@
+use crate::{Money, Obligation, Obligations, Person, SolverError};
+
+/// Restrictions on who may pay whom directly, applied by [`crate::Solver::with_constraints`].
+/// Pairs are unordered: forbidding `(A, B)` also forbids `B` paying `A`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Constraints {
+    forbidden: Vec<(Person, Person)>,
+    preferred: Vec<(Person, Person)>,
+}
+
+impl Constraints {
+    #[inline(always)]
+    pub fn builder() -> ConstraintsBuilder {
+        ConstraintsBuilder::default()
+    }
+
+    #[inline(always)]
+    pub fn forbidden(&self) -> &[(Person, Person)] {
+        &self.forbidden
+    }
+
+    #[inline(always)]
+    pub fn preferred(&self) -> &[(Person, Person)] {
+        &self.preferred
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.forbidden.is_empty() && self.preferred.is_empty()
+    }
+
+    #[inline(always)]
+    pub fn to_builder(&self) -> ConstraintsBuilder {
+        ConstraintsBuilder {
+            forbidden: self.forbidden.clone(),
+            preferred: self.preferred.clone(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn allows(&self, a: &Person, b: &Person) -> bool {
+        !self
+            .forbidden
+            .iter()
+            .any(|(x, y)| (x == a && y == b) || (x == b && y == a))
+    }
+
+    /// The transfers settling preferred pairs between themselves, as far as their `balances` allow.
+    pub(crate) fn settle_preferred(&self, balances: &[(Person, Money)]) -> Obligations {
+        let mut balances = balances
+            .iter()
+            .map(|(person, balance)| (person.clone(), balance.raw()))
+            .collect::<Vec<_>>();
+        let mut obligations = Obligations::builder();
+
+        let index = |balances: &[(Person, i32)], person: &Person| {
+            balances.iter().position(|(p, _)| p == person)
+        };
+
+        for (a, b) in &self.preferred {
+            if let (Some(a), Some(b)) = (index(&balances, a), index(&balances, b)) {
+                let (from, to) = if balances[a].1 < 0 { (a, b) } else { (b, a) };
+                let amount = (-balances[from].1).min(balances[to].1);
+
+                if amount > 0 && self.allows(&balances[from].0, &balances[to].0) {
+                    balances[from].1 += amount;
+                    balances[to].1 -= amount;
+
+                    obligations.record(
+                        Obligation::builder()
+                            .from(balances[from].0.clone())
+                            .to(balances[to].0.clone())
+                            .amount(Money::new(amount))
+                            .build(),
+                    );
+                }
+            }
+        }
+
+        obligations.build()
+    }
+
+    /// The shortest chain from `from` to `to` through `people` in which every two in a row may transact,
+    /// both ends included. On the first hop, the people `rank` puts highest are tried first.
+    fn route(
+        &self,
+        from: &Person,
+        to: &Person,
+        people: &[Person],
+        rank: impl Fn(&Person) -> usize,
+    ) -> Option<Vec<Person>> {
+        let mut nodes = vec![from.clone(), to.clone()];
+        nodes.extend(people.iter().filter(|p| *p != from && *p != to).cloned());
+
+        let mut previous: Vec<Option<usize>> = vec![None; nodes.len()];
+        let mut queue = std::collections::VecDeque::from([0]);
+
+        while let Some(at) = queue.pop_front() {
+            let mut next = (1..nodes.len())
+                .filter(|n| previous[*n].is_none() && self.allows(&nodes[at], &nodes[*n]))
+                .collect::<Vec<_>>();
+            if at == 0 {
+                next.sort_by_key(|n| std::cmp::Reverse(rank(&nodes[*n])));
+            }
+
+            for n in next {
+                previous[n] = Some(at);
+                if n == 1 {
+                    let mut route = vec![to.clone()];
+                    let mut at = 1;
+                    while let Some(p) = previous[at] {
+                        route.push(nodes[p].clone());
+                        at = p;
+                    }
+                    route.reverse();
+                    return Some(route);
+                }
+                queue.push_back(n);
+            }
+        }
+
+        None
+    }
+
+    /// Reroutes every transfer between a forbidden pair along the shortest chain of people who may transact,
+    /// favouring someone who already pays or is paid by either side, and leaves the other transfers alone.
+    /// Rerouted transfers are netted into the existing ones, whose sources are dropped.
+    pub(crate) fn reroute(
+        &self,
+        obligations: &Obligations,
+        people: &[Person],
+    ) -> Result<Obligations, SolverError> {
+        let mut obligations = obligations.clone();
+
+        while let Some(o) = obligations
+            .iter()
+            .find(|o| !self.allows(&o.from, &o.to))
+            .cloned()
+        {
+            let pays = |a: &Person, b: &Person| !obligations.between(a, b).is_empty();
+
+            let route = self
+                .route(&o.from, &o.to, people, |via| {
+                    usize::from(pays(&o.from, via)) + usize::from(pays(via, &o.to))
+                })
+                .ok_or_else(|| {
+                    SolverError::UnsatisfiableConstraints(o.from.clone(), o.to.clone())
+                })?;
+            let via = &route[1..route.len() - 1];
+
+            let rerouted = route
+                .windows(2)
+                .map(|hop| {
+                    Obligation::builder()
+                        .from(hop[0].clone())
+                        .to(hop[1].clone())
+                        .amount(o.amount)
+                        .build()
+                })
+                .collect::<Vec<_>>();
+
+            let rest = obligations.filter(|t| !(t.from == o.from && t.to == o.to));
+            obligations = rest
+                .merge(&Obligations::new(&rerouted))
+                .into_iter()
+                .map(|mut t| {
+                    if via.contains(&t.from) || via.contains(&t.to) {
+                        t.sources.clear();
+                    }
+                    t
+                })
+                .collect();
+        }
+
+        Ok(obligations)
+    }
+
+    /// Settles the balances with as few transfers as it can, never between forbidden pairs.
+    /// Preferred pairs are settled first; when a debtor is forbidden from paying every remaining creditor,
+    /// the debt is routed along the shortest chain of people who may transact.
+    pub(crate) fn settle(&self, balances: &[(Person, Money)]) -> Result<Obligations, SolverError> {
+        let preferred = self.settle_preferred(balances);
+
+        let mut balances = balances
+            .iter()
+            .map(|(person, balance)| (person.clone(), balance.raw()))
+            .collect::<Vec<_>>();
+        let mut transfers: Vec<(usize, usize, i32)> = Vec::new();
+
+        let index = |balances: &[(Person, i32)], person: &Person| {
+            balances.iter().position(|(p, _)| p == person)
+        };
+
+        let mut transfer = |balances: &mut [(Person, i32)], from: usize, to: usize, amount: i32| {
+            balances[from].1 += amount;
+            balances[to].1 -= amount;
+
+            match transfers
+                .iter_mut()
+                .find(|(f, t, _)| *f == from && *t == to)
+            {
+                Some((_, _, existing)) => *existing += amount,
+                None => transfers.push((from, to, amount)),
+            }
+        };
+
+        for o in &preferred {
+            if let (Some(from), Some(to)) = (index(&balances, &o.from), index(&balances, &o.to)) {
+                transfer(&mut balances, from, to, o.amount.raw());
+            }
+        }
+
+        // Largest debtor first, paying the creditor that clears them exactly, or else the largest one.
+        while let Some(from) = largest(&balances, |b| -b) {
+            let debt = -balances[from].1;
+            let allowed = |to: usize| self.allows(&balances[from].0, &balances[to].0);
+
+            let creditors = (0..balances.len())
+                .filter(|to| balances[*to].1 > 0)
+                .collect::<Vec<_>>();
+
+            let direct = creditors
+                .iter()
+                .copied()
+                .filter(|to| allowed(*to))
+                .max_by_key(|to| (balances[*to].1 == debt, balances[*to].1, usize::MAX - to));
+
+            if let Some(to) = direct {
+                let amount = debt.min(balances[to].1);
+                transfer(&mut balances, from, to, amount);
+                continue;
+            }
+
+            let people = balances.iter().map(|(p, _)| p.clone()).collect::<Vec<_>>();
+            let route = creditors.iter().find_map(|to| {
+                self.route(&people[from], &people[*to], &people, |_| 0)
+                    .map(|route| (route, *to))
+            });
+
+            match route {
+                Some((route, to)) => {
+                    let amount = debt.min(balances[to].1);
+                    for hop in route.windows(2) {
+                        let (Some(a), Some(b)) =
+                            (index(&balances, &hop[0]), index(&balances, &hop[1]))
+                        else {
+                            continue;
+                        };
+                        transfer(&mut balances, a, b, amount);
+                    }
+                }
+                None => {
+                    let to = creditors.first().map_or(from, |to| *to);
+
+                    return Err(SolverError::UnsatisfiableConstraints(
+                        balances[from].0.clone(),
+                        balances[to].0.clone(),
+                    ));
+                }
+            }
+        }
+
+        let mut obligations = Obligations::builder();
+
+        for (from, to, amount) in transfers {
+            obligations.record(
+                Obligation::builder()
+                    .from(balances[from].0.clone())
+                    .to(balances[to].0.clone())
+                    .amount(Money::new(amount))
+                    .build(),
+            );
+        }
+
+        Ok(obligations.build())
+    }
+}
+
+/// Index of the entry with the largest positive `key(balance)`, the earliest one on ties.
+fn largest(balances: &[(Person, i32)], key: impl Fn(i32) -> i32) -> Option<usize> {
+    balances
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, balance))| key(*balance) > 0)
+        .max_by_key(|(i, (_, balance))| (key(*balance), usize::MAX - i))
+        .map(|(i, _)| i)
+}
+
+#[derive(Debug, Default)]
+pub struct ConstraintsBuilder {
+    forbidden: Vec<(Person, Person)>,
+    preferred: Vec<(Person, Person)>,
+}
+
+impl ConstraintsBuilder {
+    /// `a` and `b` must never pay each other directly.
+    #[inline(always)]
+    pub fn forbid(&mut self, a: Person, b: Person) -> &mut Self {
+        self.forbidden.push((a, b));
+        self
+    }
+
+    /// Whenever one of `a` and `b` owes money and the other is owed, settle between them first.
+    #[inline(always)]
+    pub fn prefer(&mut self, a: Person, b: Person) -> &mut Self {
+        self.preferred.push((a, b));
+        self
+    }
+
+    #[inline(always)]
+    pub fn build(&mut self) -> Constraints {
+        Constraints {
+            forbidden: self.forbidden.clone(),
+            preferred: self.preferred.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ObligationBuilder, Payment, Payments};
+
+    #[test]
+    fn test_forbidden_and_preferred_pairs() {
+        let (a, b, c, d) = (
+            Person::new("A"),
+            Person::new("B"),
+            Person::new("C"),
+            Person::new("D"),
+        );
+
+        let payments = Payments::builder()
+            .record(Payment::new(
+                a.clone(),
+                Money::new(4000),
+                &[a.clone(), b.clone(), c.clone(), d.clone()],
+            ))
+            .record(Payment::new(
+                b.clone(),
+                Money::new(2000),
+                &[a.clone(), b.clone(), c.clone(), d.clone()],
+            ))
+            .build();
+
+        // A is owed 2500, B is owed 500, C and D owe 1500 each.
+        let forbidden = Constraints::builder()
+            .forbid(c.clone(), a.clone())
+            .forbid(d.clone(), a.clone())
+            .build();
+
+        let obligations = payments
+            .solver()
+            .with_constraints(forbidden.clone())
+            .solve()
+            .unwrap();
+
+        let sorted = |mut balances: Vec<(Person, Money)>| {
+            balances.sort_by_key(|(p, _)| p.raw().clone());
+            balances
+        };
+
+        assert!(obligations
+            .raw()
+            .iter()
+            .all(|o| forbidden.allows(&o.from, &o.to)));
+        assert_eq!(sorted(payments.balances()), sorted(obligations.balances()));
+        // Only the forbidden transfers are rerouted, through B, and it still takes three.
+        assert_eq!(3, payments.who_pays_whom().unwrap().len());
+        assert_eq!(3, obligations.len());
+
+        // C may only pay D, another debtor, who passes it on.
+        let through_d = Constraints::builder()
+            .forbid(c.clone(), a.clone())
+            .forbid(c.clone(), b.clone())
+            .build();
+        let obligations = payments
+            .solver()
+            .with_constraints(through_d)
+            .solve()
+            .unwrap();
+
+        assert_eq!(
+            Obligations::new(&[
+                ObligationBuilder::new(c.clone(), d.clone(), Money::new(1500)).build(),
+                ObligationBuilder::new(d.clone(), a.clone(), Money::new(2500)).build(),
+                ObligationBuilder::new(d.clone(), b.clone(), Money::new(500)).build(),
+            ]),
+            obligations
+        );
+
+        let preferred = Constraints::builder().prefer(d.clone(), b.clone()).build();
+        let obligations = payments
+            .solver()
+            .with_constraints(preferred)
+            .solve()
+            .unwrap();
+
+        assert!(obligations.raw().contains(
+            &Obligation::builder()
+                .from(d.clone())
+                .to(b.clone())
+                .amount(Money::new(500))
+                .build()
+        ));
+
+        let impossible = Constraints::builder()
+            .forbid(c.clone(), a.clone())
+            .forbid(c.clone(), b.clone())
+            .forbid(c.clone(), d.clone())
+            .build();
+
+        assert!(matches!(
+            payments.solver().with_constraints(impossible).solve(),
+            Err(SolverError::UnsatisfiableConstraints(from, _)) if from == c
+        ));
+
+        // Nobody may transact with both C and A, but C can still reach A through D then E.
+        let e = Person::new("E");
+        let payments = Payments::builder()
+            .record(Payment::new(
+                a.clone(),
+                Money::new(3000),
+                &[a.clone(), c.clone()],
+            ))
+            .record(Payment::new(
+                d.clone(),
+                Money::new(1000),
+                &[d.clone(), e.clone()],
+            ))
+            .build();
+        let chain = Constraints::builder()
+            .forbid(c.clone(), a.clone())
+            .forbid(c.clone(), e.clone())
+            .forbid(d.clone(), a.clone())
+            .build();
+        let obligations = payments
+            .solver()
+            .with_constraints(chain.clone())
+            .solve()
+            .unwrap();
+
+        assert_eq!(
+            Obligations::new(&[
+                ObligationBuilder::new(c.clone(), d.clone(), Money::new(1500)).build(),
+                ObligationBuilder::new(d.clone(), e.clone(), Money::new(1000)).build(),
+                ObligationBuilder::new(e.clone(), a.clone(), Money::new(1500)).build(),
+            ]),
+            obligations
+        );
+        assert_eq!(sorted(payments.balances()), sorted(obligations.balances()));
+
+        let settled = chain.settle(&payments.balances()).unwrap();
+        assert!(settled.raw().iter().all(|o| chain.allows(&o.from, &o.to)));
+        assert_eq!(sorted(payments.balances()), sorted(settled.balances()));
+    }
+}
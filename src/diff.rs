@@ -0,0 +1,197 @@
+use crate::{Money, Obligation, Obligations, Person};
+
+/// What changed between two settlements, returned by [`Obligations::diff`].
+///
+/// Transfers are matched by who pays whom: several transfers between the same two people,
+/// such as the ones split by [`crate::TransferLimits`], count as a single one.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ObligationsDiff {
+    added: Vec<Obligation>,
+    removed: Vec<Obligation>,
+    changed: Vec<(Obligation, Obligation)>,
+    balances: Vec<(Person, Money)>,
+}
+
+impl ObligationsDiff {
+    /// Transfers only found in the new settlement.
+    #[inline(always)]
+    pub fn added(&self) -> &[Obligation] {
+        &self.added
+    }
+
+    /// Transfers only found in the old settlement.
+    #[inline(always)]
+    pub fn removed(&self) -> &[Obligation] {
+        &self.removed
+    }
+
+    /// Transfers between the same people whose amount or method changed, as `(old, new)`.
+    #[inline(always)]
+    pub fn changed(&self) -> &[(Obligation, Obligation)] {
+        &self.changed
+    }
+
+    /// How much each person's net balance moved, for the ones whose balance did.
+    #[inline(always)]
+    pub fn balances(&self) -> &[(Person, Money)] {
+        &self.balances
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Everyone who pays or is paid by a transfer that was added, removed or changed.
+    pub fn affected(&self) -> Vec<Person> {
+        let mut people: Vec<Person> = Vec::new();
+
+        let transfers = self
+            .added
+            .iter()
+            .chain(&self.removed)
+            .chain(self.changed.iter().map(|(_, new)| new));
+
+        for o in transfers {
+            for person in [&o.from, &o.to] {
+                if !people.contains(person) {
+                    people.push(person.clone());
+                }
+            }
+        }
+
+        people.sort();
+        people
+    }
+}
+
+impl Obligations {
+    /// Compares this settlement with a `new` one, such as the result of solving again after editing a payment.
+    pub fn diff(&self, new: &Obligations) -> ObligationsDiff {
+        let (old, new_pairs) = (pairs(self), pairs(new));
+        let mut diff = ObligationsDiff::default();
+
+        for o in &old {
+            match new_pairs.iter().find(|n| n.from == o.from && n.to == o.to) {
+                Some(n) if (n.amount, n.method()) != (o.amount, o.method()) => {
+                    diff.changed.push((o.clone(), n.clone()))
+                }
+                Some(_) => {}
+                None => diff.removed.push(o.clone()),
+            }
+        }
+
+        diff.added = new_pairs
+            .into_iter()
+            .filter(|n| !old.iter().any(|o| n.from == o.from && n.to == o.to))
+            .collect();
+
+        let old_balances = self.balances();
+        let balance = |balances: &[(Person, Money)], person: &Person| {
+            balances
+                .iter()
+                .find(|(p, _)| p == person)
+                .map_or(0, |(_, b)| b.raw())
+        };
+
+        let new_balances = new.balances();
+        for (person, _) in old_balances.iter().chain(&new_balances) {
+            let change = balance(&new_balances, person) - balance(&old_balances, person);
+
+            if change != 0 && !diff.balances.iter().any(|(p, _)| p == person) {
+                diff.balances.push((person.clone(), Money::new(change)));
+            }
+        }
+
+        diff
+    }
+}
+
+/// Adds up the transfers between the same two people, in the same direction.
+/// A payment method is kept only when all of them agree on it.
+fn pairs(obligations: &Obligations) -> Vec<Obligation> {
+    let mut pairs: Vec<Obligation> = Vec::new();
+
+    for o in obligations {
+        match pairs.iter_mut().find(|p| p.from == o.from && p.to == o.to) {
+            Some(p) => {
+                p.amount = Money::new(p.amount.raw() + o.amount.raw());
+                if p.method != o.method {
+                    p.method = None;
+                }
+            }
+            None => pairs.push(o.clone()),
+        }
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Payment, Payments};
+
+    #[test]
+    fn test_diff_after_editing_a_payment() {
+        let (a, b, c, d) = (
+            Person::new("A"),
+            Person::new("B"),
+            Person::new("C"),
+            Person::new("D"),
+        );
+
+        let mut payments = Payments::builder()
+            .record(Payment::new(
+                a.clone(),
+                Money::new(3000),
+                &[a.clone(), b.clone()],
+            ))
+            .record(Payment::new(
+                c.clone(),
+                Money::new(1000),
+                &[c.clone(), d.clone()],
+            ))
+            .build();
+
+        let old = payments.who_pays_whom().unwrap();
+        assert!(old.diff(&old).is_empty());
+
+        payments.replace(
+            0,
+            Payment::new(a.clone(), Money::new(4000), &[a.clone(), b.clone()]),
+        );
+        let new = payments.who_pays_whom().unwrap();
+        let diff = old.diff(&new);
+
+        assert!(diff.added().is_empty() && diff.removed().is_empty());
+        assert_eq!(
+            vec![(
+                Obligation::builder()
+                    .from(b.clone())
+                    .to(a.clone())
+                    .amount(Money::new(1500))
+                    .build(),
+                Obligation::builder()
+                    .from(b.clone())
+                    .to(a.clone())
+                    .amount(Money::new(2000))
+                    .build(),
+            )],
+            diff.changed()
+        );
+        assert_eq!(vec![a.clone(), b.clone()], diff.affected());
+        assert_eq!(
+            vec![(b.clone(), Money::new(-500)), (a.clone(), Money::new(500))],
+            diff.balances()
+        );
+
+        // Where a transfer comes from is no change.
+        let traced = payments.who_pays_whom_with_provenance().unwrap();
+        assert!(new.diff(&traced).is_empty());
+
+        let diff = new.diff(&Obligations::new(&[]));
+        assert_eq!(2, diff.removed().len());
+        assert_eq!(vec![a, b, c, d], diff.affected());
+    }
+}
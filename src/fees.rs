@@ -0,0 +1,252 @@
+use chrono::{Days, NaiveDate};
+
+use crate::{Money, Obligations, Payment, PaymentId, Person};
+
+/// The category of the payments charging late fees, see [`crate::Ledger::accrue`].
+pub const LATE_FEE_CATEGORY: &str = "late fee";
+
+/// What is charged on a debt once it is overdue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LateCharge {
+    /// Simple interest at this yearly rate, e.g. 0.05 for 5%, accrued for every day the debt is overdue.
+    Interest(f64),
+    /// A one-off fee, charged when the debt becomes overdue.
+    Flat(Money),
+}
+
+/// A policy charging whoever has owed money for more than a grace period, see [`crate::Ledger::with_late_fees`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LateFees {
+    charge: LateCharge,
+    grace: u64,
+}
+
+impl LateFees {
+    /// Simple interest at a yearly `rate`, e.g. 0.05 for 5%, on debts older than 30 days.
+    #[inline(always)]
+    pub const fn interest(rate: f64) -> Self {
+        Self {
+            charge: LateCharge::Interest(rate),
+            grace: 30,
+        }
+    }
+
+    /// A one-off `fee` on debts older than 30 days.
+    #[inline(always)]
+    pub const fn flat(fee: Money) -> Self {
+        Self {
+            charge: LateCharge::Flat(fee),
+            grace: 30,
+        }
+    }
+
+    /// Debts are overdue once they have been owed for more than `days`.
+    #[inline(always)]
+    pub const fn after(mut self, days: u64) -> Self {
+        self.grace = days;
+        self
+    }
+
+    #[inline(always)]
+    pub const fn charge(&self) -> LateCharge {
+        self.charge
+    }
+
+    #[inline(always)]
+    pub const fn grace(&self) -> u64 {
+        self.grace
+    }
+
+    /// The first day a debt owed since `since` is overdue.
+    #[inline(always)]
+    pub fn overdue_from(&self, since: NaiveDate) -> Option<NaiveDate> {
+        since.checked_add_days(Days::new(self.grace + 1))
+    }
+
+    /// The charges on `transfers`, all owed by someone since `since`, for the days after `accrued` up to `on`.
+    ///
+    /// Each one is a payment of the charge made by the creditor, which the debtor takes all of.
+    pub(crate) fn charges(
+        &self,
+        transfers: &Obligations,
+        since: NaiveDate,
+        accrued: Option<NaiveDate>,
+        on: NaiveDate,
+    ) -> Vec<Payment> {
+        let Some(overdue) = self.overdue_from(since) else {
+            return Vec::new();
+        };
+
+        let amounts = match self.charge {
+            LateCharge::Interest(rate) => {
+                let start = accrued
+                    .and_then(|accrued| accrued.succ_opt())
+                    .map_or(overdue, |next| next.max(overdue));
+                let days = (on - start).num_days() + 1;
+
+                if days <= 0 {
+                    return Vec::new();
+                }
+
+                transfers
+                    .iter()
+                    .map(|t| {
+                        let interest = f64::from(t.amount.raw()) * rate * days as f64 / 365.0;
+                        (
+                            interest.round() as i32,
+                            format!("Interest on {} over {days} days", t.amount),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            }
+            LateCharge::Flat(fee) => {
+                if on < overdue || accrued.is_some_and(|accrued| accrued >= overdue) {
+                    return Vec::new();
+                }
+
+                // Split the fee between the creditors, the remainder going to the first one.
+                let total = transfers.total().raw().max(1);
+                let mut parts = transfers
+                    .iter()
+                    .map(|t| {
+                        (i64::from(fee.raw()) * i64::from(t.amount.raw()) / i64::from(total)) as i32
+                    })
+                    .collect::<Vec<_>>();
+                let remainder = fee.raw() - parts.iter().sum::<i32>();
+                if let Some(first) = parts.first_mut() {
+                    *first += remainder;
+                }
+
+                parts
+                    .into_iter()
+                    .map(|part| (part, format!("Late fee on debts since {since}")))
+                    .collect()
+            }
+        };
+
+        transfers
+            .iter()
+            .zip(amounts)
+            .filter(|(_, (amount, _))| *amount > 0)
+            .map(|(t, (amount, description))| {
+                Payment::builder()
+                    .from(t.to.clone())
+                    .amount(Money::new(amount))
+                    .to(std::slice::from_ref(&t.from))
+                    .weight(t.from.clone(), 1)
+                    .id(PaymentId::new(format!("late-fee:{}:{}@{on}", t.from, t.to)))
+                    .date(on)
+                    .description(description)
+                    .category(LATE_FEE_CATEGORY)
+                    .build()
+            })
+            .collect()
+    }
+}
+
+/// Everyone who owes money after `events`, with the day they started owing it: the last day their
+/// balance went negative. `events` are balance changes, sorted by date.
+pub(crate) fn owing_since(
+    events: &[(NaiveDate, Vec<(Person, Money)>)],
+) -> Vec<(Person, NaiveDate)> {
+    let mut balances: Vec<(Person, i32, Option<NaiveDate>)> = Vec::new();
+
+    for (date, changes) in events {
+        for (person, change) in changes {
+            let index = match balances.iter().position(|(p, _, _)| p == person) {
+                Some(index) => index,
+                None => {
+                    balances.push((person.clone(), 0, None));
+                    balances.len() - 1
+                }
+            };
+
+            let (_, balance, since) = &mut balances[index];
+            let owed = *balance < 0;
+            *balance += change.raw();
+
+            match (owed, *balance < 0) {
+                (false, true) => *since = Some(*date),
+                (true, false) => *since = None,
+                _ => {}
+            }
+        }
+    }
+
+    balances
+        .into_iter()
+        .filter_map(|(person, _, since)| since.map(|since| (person, since)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Ledger;
+
+    #[test]
+    fn test_late_fees() {
+        let (a, b) = (Person::new("A"), Person::new("B"));
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        let paid = |from: &Person, amount, m, d| {
+            Payment::builder()
+                .from(from.clone())
+                .amount(Money::new(amount))
+                .to(&[a.clone(), b.clone()])
+                .date(date(m, d))
+                .build()
+        };
+
+        // B owes 50.00 since January 1st, overdue from February 1st on.
+        let mut ledger = Ledger::default().with_late_fees(LateFees::interest(0.10));
+        ledger.record(paid(&a, 10000, 1, 1));
+
+        assert!(ledger.accrue(date(1, 31)).unwrap().is_empty());
+
+        // 30 days at 10% a year on 50.00 is 0.41, and the interest is simple: the next 30 days accrue on 50.00 again.
+        let charges = ledger.accrue(date(3, 1)).unwrap();
+        assert_eq!(1, charges.len());
+        assert_eq!(
+            (&a, Money::new(41)),
+            (charges[0].from(), charges[0].amount())
+        );
+        assert_eq!(
+            vec![(b.clone(), Money::new(41)), (a.clone(), Money::new(0))],
+            charges[0].shares()
+        );
+        assert_eq!(Some(LATE_FEE_CATEGORY), charges[0].category());
+        assert_eq!(Money::new(5041), ledger.who_pays_whom().unwrap().total());
+
+        ledger.accrue(date(3, 31)).unwrap();
+        assert_eq!(Money::new(5082), ledger.who_pays_whom().unwrap().total());
+
+        // Once B pays back 30.00, interest only accrues on the 20.00 left: 0.16 over 30 days.
+        ledger.record(
+            Payment::builder()
+                .from(b.clone())
+                .amount(Money::new(3000))
+                .to(std::slice::from_ref(&a))
+                .weight(a.clone(), 1)
+                .date(date(4, 1))
+                .build(),
+        );
+        let charges = ledger.accrue(date(4, 30)).unwrap();
+        assert_eq!(Money::new(16), charges[0].amount());
+        assert_eq!(Money::new(2098), ledger.who_pays_whom().unwrap().total());
+
+        // A flat fee is charged once, and not for debts that were settled in time.
+        let mut ledger =
+            Ledger::default().with_late_fees(LateFees::flat(Money::new(500)).after(14));
+        ledger.record(paid(&a, 10000, 1, 1));
+        ledger.record(paid(&b, 10000, 1, 10));
+
+        assert!(ledger.accrue(date(1, 20)).unwrap().is_empty());
+        ledger.record(paid(&b, 4000, 1, 21));
+        assert!(ledger.accrue(date(2, 4)).unwrap().is_empty());
+        assert_eq!(1, ledger.accrue(date(2, 5)).unwrap().len());
+        assert!(ledger.accrue(date(3, 1)).unwrap().is_empty());
+        assert_eq!(Money::new(2500), ledger.who_pays_whom().unwrap().total());
+    }
+}
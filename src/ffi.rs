@@ -0,0 +1,259 @@
+//! C bindings, see `include/pagat.h`, regenerated with `cbindgen --output include/pagat.h` and tested from C in `tests/ffi`.
+//!
+//! Ownership rules:
+//! - every handle returned by a `*_new` or `*_who_pays_whom` function is owned by the caller and must be
+//!   released exactly once with the matching `*_free` function;
+//! - strings passed in are borrowed for the duration of the call only, and must be NUL-terminated UTF-8;
+//! - strings handed out inside a `PagatObligation` are borrowed from the `PagatObligations` they came from,
+//!   and stay valid until that handle is freed;
+//! - a panic never unwinds into C: fallible calls return `PAGAT_ERROR_PANIC`, the others NULL or 0.
+
+use std::{
+    ffi::{c_char, CStr, CString},
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use crate::{Money, Obligations, Payment, Payments, Person, SolverError};
+
+/// Result of every fallible call; anything other than `PAGAT_ERROR_OK` means the call had no effect.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PagatError {
+    Ok = 0,
+    NullPointer,
+    InvalidUtf8,
+    OutOfBounds,
+    NoEndpointForEdge,
+    NoWeightForNode,
+    NoWeightForEdge,
+    UnsatisfiableConstraints,
+    InteriorNul,
+    Panic,
+    NeedsGroup,
+}
+
+impl From<&SolverError> for PagatError {
+    fn from(err: &SolverError) -> Self {
+        match err {
+            SolverError::NoEndpointForEdge(_) => Self::NoEndpointForEdge,
+            SolverError::NoWeightForNode(_) => Self::NoWeightForNode,
+            SolverError::NoWeightForEdge(_) => Self::NoWeightForEdge,
+            SolverError::UnsatisfiableConstraints(..) => Self::UnsatisfiableConstraints,
+            SolverError::NeedsGroup => Self::NeedsGroup,
+        }
+    }
+}
+
+/// Opaque handle to the payments recorded by a group.
+pub struct PagatPayments(Payments);
+
+/// Opaque handle to the settlement returned by `pagat_payments_who_pays_whom`.
+pub struct PagatObligations(Vec<(CString, CString, i32)>);
+
+impl TryFrom<Obligations> for PagatObligations {
+    type Error = PagatError;
+
+    fn try_from(obligations: Obligations) -> Result<Self, Self::Error> {
+        let c_string = |person: &Person| {
+            CString::new(person.raw().as_str()).map_err(|_| PagatError::InteriorNul)
+        };
+
+        obligations
+            .raw()
+            .iter()
+            .map(|o| Ok((c_string(&o.from)?, c_string(&o.to)?, o.amount.raw())))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// A single transfer: `from` pays `amount` cents to `to`.
+#[repr(C)]
+pub struct PagatObligation {
+    pub from: *const c_char,
+    pub to: *const c_char,
+    pub amount: i32,
+}
+
+unsafe fn person(name: *const c_char) -> Result<Person, PagatError> {
+    if name.is_null() {
+        return Err(PagatError::NullPointer);
+    }
+
+    CStr::from_ptr(name)
+        .to_str()
+        .map(Person::new)
+        .map_err(|_| PagatError::InvalidUtf8)
+}
+
+/// Runs `f`, returning `fallback` instead of unwinding into C if it panics.
+fn guard<T>(fallback: T, f: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(fallback)
+}
+
+/// Returns a static, NUL-terminated description of `error`.
+#[no_mangle]
+pub extern "C" fn pagat_error_message(error: PagatError) -> *const c_char {
+    let message: &'static CStr = match error {
+        PagatError::Ok => c"ok",
+        PagatError::NullPointer => c"unexpected null pointer",
+        PagatError::InvalidUtf8 => c"string is not valid UTF-8",
+        PagatError::OutOfBounds => c"index out of bounds",
+        PagatError::NoEndpointForEdge => c"could not find endpoints for edge",
+        PagatError::NoWeightForNode => c"could not find weight for node",
+        PagatError::NoWeightForEdge => c"could not find weight for edge",
+        PagatError::UnsatisfiableConstraints => c"could not settle without a forbidden transfer",
+        PagatError::NeedsGroup => c"the payment needs its group to be split",
+        PagatError::InteriorNul => c"string contains a NUL byte",
+        PagatError::Panic => c"unexpected panic",
+    };
+
+    message.as_ptr()
+}
+
+/// Creates an empty set of payments, to be released with `pagat_payments_free`.
+#[no_mangle]
+pub extern "C" fn pagat_payments_new() -> *mut PagatPayments {
+    guard(ptr::null_mut(), || {
+        Box::into_raw(Box::new(PagatPayments(Payments::default())))
+    })
+}
+
+/// Releases payments created by `pagat_payments_new`; passing NULL is a no-op.
+///
+/// # Safety
+/// `payments` must be NULL or a handle from `pagat_payments_new` that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn pagat_payments_free(payments: *mut PagatPayments) {
+    if !payments.is_null() {
+        guard((), || drop(Box::from_raw(payments)));
+    }
+}
+
+/// Records that `from` paid `amount` cents for the `to_len` people in `to`.
+///
+/// # Safety
+/// `payments` must be a live handle, `from` a NUL-terminated string and `to` an array of `to_len`
+/// NUL-terminated strings (it may be NULL when `to_len` is 0).
+#[no_mangle]
+pub unsafe extern "C" fn pagat_payments_record(
+    payments: *mut PagatPayments,
+    from: *const c_char,
+    amount: i32,
+    to: *const *const c_char,
+    to_len: usize,
+) -> PagatError {
+    guard(PagatError::Panic, || {
+        let Some(payments) = payments.as_mut() else {
+            return PagatError::NullPointer;
+        };
+
+        if to.is_null() && to_len > 0 {
+            return PagatError::NullPointer;
+        }
+
+        let from = match person(from) {
+            Ok(from) => from,
+            Err(err) => return err,
+        };
+
+        let to = match to_len {
+            0 => Ok(Vec::new()),
+            _ => slice::from_raw_parts(to, to_len)
+                .iter()
+                .map(|name| person(*name))
+                .collect::<Result<Vec<_>, _>>(),
+        };
+
+        match to {
+            Ok(to) => {
+                payments
+                    .0
+                    .record(Payment::new(from, Money::new(amount), &to));
+                PagatError::Ok
+            }
+            Err(err) => err,
+        }
+    })
+}
+
+/// Solves who pays whom, storing a new handle in `out` on success, to be released with
+/// `pagat_obligations_free`.
+///
+/// # Safety
+/// `payments` must be a live handle and `out` a valid pointer to write to.
+#[no_mangle]
+pub unsafe extern "C" fn pagat_payments_who_pays_whom(
+    payments: *const PagatPayments,
+    out: *mut *mut PagatObligations,
+) -> PagatError {
+    guard(PagatError::Panic, || {
+        let (Some(payments), false) = (payments.as_ref(), out.is_null()) else {
+            return PagatError::NullPointer;
+        };
+
+        match payments.0.who_pays_whom() {
+            Ok(obligations) => match PagatObligations::try_from(obligations) {
+                Ok(obligations) => {
+                    *out = Box::into_raw(Box::new(obligations));
+                    PagatError::Ok
+                }
+                Err(err) => err,
+            },
+            Err(err) => PagatError::from(&err),
+        }
+    })
+}
+
+/// Number of transfers in the settlement, 0 for NULL.
+///
+/// # Safety
+/// `obligations` must be NULL or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn pagat_obligations_len(obligations: *const PagatObligations) -> usize {
+    guard(0, || obligations.as_ref().map_or(0, |o| o.0.len()))
+}
+
+/// Reads the transfer at `index` into `out`; its strings are borrowed from `obligations`.
+///
+/// # Safety
+/// `obligations` must be a live handle and `out` a valid pointer to write to.
+#[no_mangle]
+pub unsafe extern "C" fn pagat_obligations_get(
+    obligations: *const PagatObligations,
+    index: usize,
+    out: *mut PagatObligation,
+) -> PagatError {
+    guard(PagatError::Panic, || {
+        let (Some(obligations), false) = (obligations.as_ref(), out.is_null()) else {
+            return PagatError::NullPointer;
+        };
+
+        match obligations.0.get(index) {
+            Some((from, to, amount)) => {
+                ptr::write(
+                    out,
+                    PagatObligation {
+                        from: from.as_ptr(),
+                        to: to.as_ptr(),
+                        amount: *amount,
+                    },
+                );
+                PagatError::Ok
+            }
+            None => PagatError::OutOfBounds,
+        }
+    })
+}
+
+/// Releases a settlement returned by `pagat_payments_who_pays_whom`; passing NULL is a no-op.
+///
+/// # Safety
+/// `obligations` must be NULL or a handle that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn pagat_obligations_free(obligations: *mut PagatObligations) {
+    if !obligations.is_null() {
+        guard((), || drop(Box::from_raw(obligations)));
+    }
+}
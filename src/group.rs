@@ -0,0 +1,346 @@
+use std::num::NonZeroU32;
+
+use chrono::NaiveDate;
+
+use crate::{
+    membership::settle_out, suggest::who_pays_next, Budget, Budgets, Membership, Money,
+    Obligations, Overrun, Payment, Payments, Person, SolverError, SolverTrace, Weights,
+};
+
+/// A set of people splitting bills together, along with the payments they recorded.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Group {
+    people: Vec<Person>,
+    payments: Payments,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Membership::is_empty")
+    )]
+    membership: Membership,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Budgets::is_empty")
+    )]
+    budgets: Budgets,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Weights::is_empty")
+    )]
+    weights: Weights,
+}
+
+impl Group {
+    #[inline(always)]
+    pub fn new(people: &[Person]) -> Self {
+        Self {
+            people: people.to_vec(),
+            payments: Payments::default(),
+            membership: Membership::default(),
+            budgets: Budgets::default(),
+            weights: Weights::default(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn people(&self) -> &[Person] {
+        &self.people
+    }
+
+    #[inline(always)]
+    pub const fn payments(&self) -> &Payments {
+        &self.payments
+    }
+
+    #[inline(always)]
+    pub const fn membership(&self) -> &Membership {
+        &self.membership
+    }
+
+    #[inline(always)]
+    pub const fn budgets(&self) -> &Budgets {
+        &self.budgets
+    }
+
+    #[inline(always)]
+    pub const fn weights(&self) -> &Weights {
+        &self.weights
+    }
+
+    /// `person` takes `weight` parts of the payments made [`crate::PaymentBuilder::weighted`],
+    /// such as their income when splitting the rent in proportion to it.
+    #[inline(always)]
+    pub fn set_weight(&mut self, person: Person, weight: NonZeroU32) -> &mut Self {
+        self.add_person(person.clone());
+        self.weights.set(person, weight);
+        self
+    }
+
+    /// Everyone sharing a weighted payment without having a weight, see [`Weights::missing`].
+    #[inline(always)]
+    pub fn missing_weights(&self) -> Vec<Person> {
+        self.weights
+            .missing(&self.membership.resolve(&self.people, &self.payments))
+    }
+
+    /// Adds `budget`, replacing the one limiting the same spending if there was any.
+    #[inline(always)]
+    pub fn set_budget(&mut self, budget: Budget) -> &mut Self {
+        self.budgets.set(budget);
+        self
+    }
+
+    /// Every budget the recorded payments go over.
+    #[inline(always)]
+    pub fn overruns(&self) -> Vec<Overrun> {
+        self.budgets.overruns(&self.resolved_payments())
+    }
+
+    /// The budgets recording `payment` would go over, or further over, to check before recording it.
+    pub fn check(&self, payment: &Payment) -> Vec<Overrun> {
+        let before = self.overruns();
+        let mut after = self.clone();
+        after.record(payment.clone());
+
+        after
+            .overruns()
+            .into_iter()
+            .filter(|overrun| {
+                before
+                    .iter()
+                    .all(|b| b.budget != overrun.budget || b.spent < overrun.spent)
+            })
+            .collect()
+    }
+
+    #[inline(always)]
+    pub fn add_person(&mut self, person: Person) -> &mut Self {
+        if !self.people.contains(&person) {
+            self.people.push(person);
+        }
+        self
+    }
+
+    /// `person` joins the group on `date`, and shares the payments made for its members from then on.
+    #[inline(always)]
+    pub fn join(&mut self, person: Person, date: NaiveDate) -> &mut Self {
+        self.add_person(person.clone());
+        self.membership.join(person, date);
+        self
+    }
+
+    /// `person` leaves the group after `date`, see [`Group::settle_out`] to settle their part.
+    #[inline(always)]
+    pub fn leave(&mut self, person: Person, date: NaiveDate) -> &mut Self {
+        self.add_person(person.clone());
+        self.membership.leave(person, date);
+        self
+    }
+
+    /// The payments, with the ones made for the group's members split among the members on their date,
+    /// the weighted ones split by the group's weights, and everyone capped at what is left of their budget.
+    #[inline(always)]
+    pub fn resolved_payments(&self) -> Payments {
+        self.budgets.resolve(
+            &self
+                .weights
+                .resolve(&self.membership.resolve(&self.people, &self.payments)),
+        )
+    }
+
+    /// Records a payment, adding anyone involved in it to the group.
+    #[inline(always)]
+    pub fn record(&mut self, payment: Payment) -> &mut Self {
+        self.add_people_of(&payment);
+        self.payments.record(payment);
+        self
+    }
+
+    /// Replaces the payment at `index`, adding anyone involved in the new one to the group.
+    #[inline(always)]
+    pub fn replace(&mut self, index: usize, payment: Payment) -> Option<Payment> {
+        if index >= self.payments.raw().len() {
+            return None;
+        }
+
+        self.add_people_of(&payment);
+        self.payments.replace(index, payment)
+    }
+
+    #[inline(always)]
+    pub fn remove(&mut self, index: usize) -> Option<Payment> {
+        self.payments.remove(index)
+    }
+
+    #[inline(always)]
+    fn add_people_of(&mut self, payment: &Payment) {
+        self.add_person(payment.from().clone());
+        for person in payment.to() {
+            self.add_person(person.clone());
+        }
+    }
+
+    /// Net balance of everyone in the group, positive when they are owed money.
+    pub fn balances(&self) -> Vec<(Person, Money)> {
+        let balances = self.resolved_payments().balances();
+
+        self.people
+            .iter()
+            .map(|person| {
+                let balance = balances
+                    .iter()
+                    .find(|(p, _)| p == person)
+                    .map(|(_, balance)| *balance)
+                    .unwrap_or_default();
+
+                (person.clone(), balance)
+            })
+            .collect()
+    }
+
+    #[inline(always)]
+    pub fn who_pays_whom(&self) -> Result<Obligations, SolverError> {
+        self.resolved_payments().who_pays_whom()
+    }
+
+    /// Updates `settlement` for one more `payment`, split the way the group would split it once recorded,
+    /// see [`Obligations::resettle`].
+    pub fn resettle(
+        &self,
+        settlement: &Obligations,
+        payment: &Payment,
+    ) -> Result<Obligations, SolverError> {
+        settlement.resettle_by(&self.delta(payment))
+    }
+
+    /// How recording `payment` would move everyone's balance, for the ones it moves.
+    fn delta(&self, payment: &Payment) -> Vec<(Person, Money)> {
+        let mut after = self.clone();
+        after.record(payment.clone());
+
+        let before = self.balances();
+        after
+            .balances()
+            .into_iter()
+            .map(|(person, balance)| {
+                let was = before
+                    .iter()
+                    .find(|(p, _)| p == &person)
+                    .map_or(0, |(_, b)| b.raw());
+
+                (person, Money::new(balance.raw() - was))
+            })
+            .filter(|(_, delta)| delta.raw() != 0)
+            .collect()
+    }
+
+    /// Same as [`Group::who_pays_whom`], along with every step the solver took.
+    #[inline(always)]
+    pub fn who_pays_whom_traced(&self) -> Result<(Obligations, SolverTrace), SolverError> {
+        self.resolved_payments().who_pays_whom_traced()
+    }
+
+    /// Who among the current members should pay the next expense they share, optionally knowing its `amount`,
+    /// split among them the way the group would split it, see [`Payments::who_pays_next`].
+    #[inline(always)]
+    pub fn who_pays_next(&self, amount: Option<Money>) -> Option<Person> {
+        let members = self.membership.members_on(&self.people, None);
+
+        who_pays_next(&self.balances(), &members, amount, |payment| {
+            self.delta(payment)
+        })
+    }
+
+    /// Only the transfers that bring `person`'s balance to zero, such as when they leave the group.
+    #[inline(always)]
+    pub fn settle_out(&self, person: &Person) -> Obligations {
+        settle_out(person, &self.balances())
+    }
+}
+
+#[cfg(feature = "serde")]
+mod file {
+    use std::{fs, io, path::Path};
+    use thiserror::Error;
+
+    use super::Group;
+
+    #[derive(Error, Debug)]
+    pub enum GroupError {
+        #[error("could not access group file: {0}")]
+        Io(#[from] io::Error),
+
+        #[error("could not parse group file: {0}")]
+        Json(#[from] serde_json::Error),
+    }
+
+    impl Group {
+        #[inline(always)]
+        pub fn from_json(json: &str) -> Result<Self, GroupError> {
+            Ok(serde_json::from_str(json)?)
+        }
+
+        #[inline(always)]
+        pub fn to_json(&self) -> Result<String, GroupError> {
+            Ok(serde_json::to_string_pretty(self)?)
+        }
+
+        /// Reads a group from a JSON file, or returns an empty group if the file does not exist yet.
+        pub fn load(path: impl AsRef<Path>) -> Result<Self, GroupError> {
+            match fs::read_to_string(path) {
+                Ok(json) => Self::from_json(&json),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+                Err(err) => Err(err.into()),
+            }
+        }
+
+        #[inline(always)]
+        pub fn save(&self, path: impl AsRef<Path>) -> Result<(), GroupError> {
+            Ok(fs::write(path, self.to_json()?)?)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+pub use file::GroupError;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_balances() {
+        let a = Person::new("A");
+        let b = Person::new("B");
+        let c = Person::new("C");
+
+        let mut group = Group::new(std::slice::from_ref(&c));
+        group.record(Payment::new(
+            a.clone(),
+            Money::new(1000),
+            std::slice::from_ref(&b),
+        ));
+
+        assert_eq!(&[c.clone(), a.clone(), b.clone()], group.people());
+        assert_eq!(
+            vec![
+                (c, Money::new(0)),
+                (a, Money::new(500)),
+                (b, Money::new(-500))
+            ],
+            group.balances()
+        );
+
+        #[cfg(feature = "serde")]
+        {
+            let json = group.to_json().unwrap();
+            let parsed = Group::from_json(&json).unwrap();
+
+            assert_eq!(group.people(), parsed.people());
+            assert_eq!(
+                group.who_pays_whom().unwrap(),
+                parsed.who_pays_whom().unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use chrono::NaiveDate;
+
+use crate::{
+    fees::owing_since, LateFees, Money, Obligations, Payment, Payments, Person, Residuals, Solver,
+    SolverError, Weights, LATE_FEE_CATEGORY,
+};
+
+/// A closed settlement period, see [`Ledger::close`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Statement {
+    /// The day after the previous period closed, if there was one.
+    pub from: Option<NaiveDate>,
+    /// The last day of the period.
+    pub until: NaiveDate,
+    /// What was left unsettled by the previous period.
+    pub opening: Residuals,
+    /// The payments made during the period, undated ones included.
+    pub payments: Payments,
+    /// The transfers that settled the period, recorded as paid.
+    pub settlement: Obligations,
+    /// What was left unsettled, carried into the next period.
+    pub closing: Residuals,
+}
+
+/// Payments kept in settlement periods: each period is solved and closed on a date,
+/// and whatever it leaves unsettled is carried into the next one as its opening balances.
+#[derive(Debug, Default, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ledger {
+    payments: Payments,
+    opening: Residuals,
+    statements: Vec<Statement>,
+    threshold: Option<Money>,
+    late_fees: Option<LateFees>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Weights::is_empty")
+    )]
+    weights: Weights,
+    /// The last day late fees were accrued up to.
+    accrued: Option<NaiveDate>,
+}
+
+impl Ledger {
+    /// Obligations below `threshold` are left unsettled when closing a period, and carried forward.
+    #[inline(always)]
+    pub const fn with_threshold(mut self, threshold: Money) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    /// Debts left unsettled past the policy's grace period are charged, see [`Ledger::accrue`].
+    #[inline(always)]
+    pub const fn with_late_fees(mut self, late_fees: LateFees) -> Self {
+        self.late_fees = Some(late_fees);
+        self
+    }
+
+    /// The payments made [`crate::PaymentBuilder::weighted`] are split by `weights`, see [`Weights::resolve`].
+    #[inline(always)]
+    pub fn with_weights(mut self, weights: Weights) -> Self {
+        self.weights = weights;
+        self
+    }
+
+    /// The payments of the open period, and the ones dated after it.
+    #[inline(always)]
+    pub const fn payments(&self) -> &Payments {
+        &self.payments
+    }
+
+    /// What the last closed period left unsettled.
+    #[inline(always)]
+    pub const fn opening(&self) -> &Residuals {
+        &self.opening
+    }
+
+    /// Every closed period, oldest first.
+    #[inline(always)]
+    pub fn statements(&self) -> &[Statement] {
+        &self.statements
+    }
+
+    #[inline(always)]
+    pub fn record(&mut self, payment: Payment) {
+        self.payments.record(payment);
+    }
+
+    /// Net balance of everyone in the open period, opening balances included.
+    pub fn balances(&self) -> Vec<(Person, Money)> {
+        let mut balances = self.weights.resolve(&self.payments).balances();
+
+        for (person, amount) in self.opening.balances() {
+            match balances.iter_mut().find(|(p, _)| *p == person) {
+                Some((_, balance)) => *balance = Money::new(balance.raw() + amount.raw()),
+                None => balances.push((person, amount)),
+            }
+        }
+
+        balances
+    }
+
+    /// Who pays whom to settle the open period, opening balances included.
+    #[inline(always)]
+    pub fn who_pays_whom(&self) -> Result<Obligations, SolverError> {
+        self.solver(&self.payments).solve()
+    }
+
+    /// Charges the late fees due on `on`, records them as payments and returns them.
+    ///
+    /// Someone's debt is as old as the last day their balance went negative, counting the dated payments
+    /// and the balances opening the period, as of the day the previous one closed; undated payments are never late.
+    /// Interest accrues for every overdue day since the last call, on what is owed with the fees charged so far left out,
+    /// while a flat fee is only charged once per debt.
+    /// The charges go to the people the debtor pays to settle that, in the [`LATE_FEE_CATEGORY`].
+    pub fn accrue(&mut self, on: NaiveDate) -> Result<Vec<Payment>, SolverError> {
+        let Some(late_fees) = self.late_fees else {
+            return Ok(Vec::new());
+        };
+        if self.accrued.is_some_and(|accrued| accrued >= on) {
+            return Ok(Vec::new());
+        }
+
+        let mut events = Vec::new();
+        if let Some(statement) = self.statements.last() {
+            events.push((statement.until, self.opening.balances()));
+        }
+        for payment in self.weights.resolve(&self.payments).raw() {
+            if let Some(date) = payment.date().filter(|date| *date <= on) {
+                events.push((
+                    date,
+                    Payments::new(std::slice::from_ref(payment)).balances(),
+                ));
+            }
+        }
+        events.sort_by_key(|(date, _)| *date);
+
+        let principal = self
+            .payments
+            .raw()
+            .iter()
+            .filter(|payment| payment.category() != Some(LATE_FEE_CATEGORY))
+            .cloned()
+            .collect::<Vec<_>>();
+        let settlement = self.solver(&Payments::new(&principal)).solve()?;
+        let charges = owing_since(&events)
+            .into_iter()
+            .flat_map(|(debtor, since)| {
+                late_fees.charges(&settlement.owed_by(&debtor), since, self.accrued, on)
+            })
+            .collect::<Vec<_>>();
+
+        for charge in &charges {
+            self.payments.record(charge.clone());
+        }
+        self.accrued = Some(on);
+
+        Ok(charges)
+    }
+
+    /// Closes the period on `until`: the payments made up to that day, and the undated ones,
+    /// are settled and the transfers recorded as paid. Later payments stay in the next period,
+    /// which opens with whatever was left unsettled.
+    pub fn close(&mut self, until: NaiveDate) -> Result<&Statement, SolverError> {
+        let (period, later): (Vec<_>, Vec<_>) = self
+            .payments
+            .raw()
+            .iter()
+            .cloned()
+            .partition(|payment| payment.date().is_none_or(|date| date <= until));
+
+        let period = Payments::new(&period);
+        let (settlement, closing) = self.solver(&period).solve_with_residuals()?;
+
+        let from = self
+            .statements
+            .last()
+            .and_then(|statement| statement.until.succ_opt());
+
+        self.statements.push(Statement {
+            from,
+            until,
+            opening: std::mem::replace(&mut self.opening, closing.clone()),
+            payments: period,
+            settlement,
+            closing,
+        });
+        self.payments = Payments::new(&later);
+
+        Ok(&self.statements[self.statements.len() - 1])
+    }
+
+    #[inline(always)]
+    fn solver(&self, payments: &Payments) -> Solver {
+        let solver = self
+            .weights
+            .resolve(payments)
+            .solver()
+            .with_residuals(&self.opening);
+
+        match self.threshold {
+            Some(threshold) => solver.with_threshold(threshold),
+            None => solver,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use super::*;
+
+    #[test]
+    fn test_monthly_statements() {
+        let (a, b) = (Person::new("A"), Person::new("B"));
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+        let paid = |from: &Person, amount, m, d| {
+            Payment::builder()
+                .from(from.clone())
+                .amount(Money::new(amount))
+                .to(&[a.clone(), b.clone()])
+                .date(date(m, d))
+                .build()
+        };
+
+        let mut ledger = Ledger::default().with_threshold(Money::new(100));
+        ledger.record(paid(&a, 3000, 1, 10));
+        ledger.record(paid(&b, 2850, 1, 20));
+        ledger.record(paid(&b, 1000, 2, 3));
+
+        // A is owed 0.75 at the end of January, too little to bother.
+        let january = ledger.close(date(1, 31)).unwrap();
+        assert!(january.settlement.is_empty());
+        assert_eq!(2, january.payments.raw().len());
+        assert_eq!(
+            vec![(b.clone(), Money::new(-75)), (a.clone(), Money::new(75))],
+            january.closing.balances()
+        );
+        assert_eq!(None, january.from);
+
+        // February opens with B owing A 0.75, and B paying for both makes up for it.
+        assert_eq!(1, ledger.payments().raw().len());
+        let february = ledger.close(date(2, 29)).unwrap();
+        assert_eq!(Some(date(2, 1)), february.from);
+        assert_eq!(
+            vec![(a.clone(), b.clone(), Money::new(425))],
+            february
+                .settlement
+                .iter()
+                .map(|o| (o.from.clone(), o.to.clone(), o.amount))
+                .collect::<Vec<_>>()
+        );
+        assert!(ledger.opening().is_empty());
+        assert_eq!(2, ledger.statements().len());
+
+        // What March leaves unsettled is carried through April, when nothing is spent, and into May.
+        ledger.record(paid(&a, 100, 3, 5));
+        let owed = vec![(b.clone(), Money::new(-50)), (a.clone(), Money::new(50))];
+
+        let march = ledger.close(date(3, 31)).unwrap();
+        assert!(march.settlement.is_empty());
+        assert_eq!(owed, march.closing.balances());
+
+        ledger.record(paid(&b, 1000, 6, 1));
+        let april = ledger.close(date(4, 30)).unwrap();
+        assert!(april.payments.raw().is_empty());
+        assert_eq!(owed, april.opening.balances());
+        assert_eq!(owed, april.closing.balances());
+
+        // June's payment is still to come, on top of what was carried over.
+        assert_eq!(owed, ledger.opening().balances());
+        assert_eq!(1, ledger.payments().raw().len());
+        assert_eq!(
+            vec![(a.clone(), Money::new(-450)), (b.clone(), Money::new(450))],
+            ledger.balances()
+        );
+
+        // Weighted payments are split by the ledger's weights.
+        let weights = Weights::new(&[
+            (a.clone(), NonZeroU32::new(3).unwrap()),
+            (b.clone(), NonZeroU32::MIN),
+        ]);
+        let mut ledger = Ledger::default().with_weights(weights);
+        ledger.record(
+            Payment::builder()
+                .from(a.clone())
+                .amount(Money::new(4000))
+                .to(&[a.clone(), b.clone()])
+                .weighted()
+                .build(),
+        );
+        assert_eq!(
+            vec![
+                (b.clone(), Money::new(-1000)),
+                (a.clone(), Money::new(1000))
+            ],
+            ledger.balances()
+        );
+    }
+}
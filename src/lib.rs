@@ -1,14 +1,53 @@
+mod budget;
+mod constraints;
+mod diff;
+mod fees;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod group;
+mod ledger;
+mod limits;
+mod membership;
 mod money;
 mod obligation;
 mod payment;
 mod person;
+mod provenance;
+#[cfg(feature = "python")]
+mod python;
+mod recurring;
+mod report;
+mod resettle;
+mod residual;
 mod solver;
+mod strategy;
+mod suggest;
+mod trace;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod weights;
 
+pub use budget::*;
+pub use constraints::*;
+pub use diff::*;
+pub use fees::*;
+pub use group::*;
+pub use ledger::*;
+pub use limits::*;
+pub use membership::*;
 pub use money::*;
 pub use obligation::*;
 pub use payment::*;
 pub use person::*;
+pub use recurring::*;
+pub use report::*;
+pub use residual::*;
 pub use solver::*;
+pub use strategy::*;
+pub use trace::*;
+pub use weights::*;
+
+pub use chrono::NaiveDate;
 
 #[cfg(test)]
 mod tests {
@@ -245,4 +284,121 @@ mod tests {
 
         assert_eq!(expected_obligations, obligations);
     }
+
+    #[test]
+    fn test_chaining_conserves_balances() {
+        let (a, b, c, d) = (
+            Person::new("A"),
+            Person::new("B"),
+            Person::new("C"),
+            Person::new("D"),
+        );
+
+        // A owes B 10.00, who owes C and D 10.00 each: A's debt can only be chained to one of them.
+        let payments = Payments::builder()
+            .record(Payment::new(
+                b.clone(),
+                Money::new(2000),
+                std::slice::from_ref(&a),
+            ))
+            .record(Payment::new(
+                c.clone(),
+                Money::new(2000),
+                std::slice::from_ref(&b),
+            ))
+            .record(Payment::new(
+                d.clone(),
+                Money::new(2000),
+                std::slice::from_ref(&b),
+            ))
+            .build();
+
+        let sorted = |mut balances: Vec<(Person, Money)>| {
+            balances.sort();
+            balances
+        };
+
+        let obligations = payments.who_pays_whom().unwrap();
+        assert_eq!(sorted(payments.balances()), sorted(obligations.balances()));
+        assert_eq!(Money::new(2000), obligations.total());
+    }
+
+    #[test]
+    fn test_canonical_order() {
+        let paid = [
+            Payment::new(
+                Person::new("A"),
+                Money::new(2000),
+                &[Person::new("B"), Person::new("C"), Person::new("H")],
+            ),
+            Payment::new(Person::new("C"), Money::new(500), &[Person::new("H")]),
+            Payment::new(
+                Person::new("B"),
+                Money::new(600),
+                &[Person::new("C"), Person::new("H")],
+            ),
+        ];
+
+        let forward = Payments::new(&paid).who_pays_whom().unwrap();
+
+        let mut reversed = paid.clone();
+        reversed.reverse();
+        let backward = Payments::new(&reversed).who_pays_whom().unwrap();
+
+        assert_eq!(forward.raw(), backward.raw());
+        assert!(forward
+            .raw()
+            .windows(2)
+            .all(|w| (&w[0].from, &w[0].to) <= (&w[1].from, &w[1].to)));
+
+        let mut shuffled = forward.raw().clone();
+        shuffled.rotate_left(1);
+        assert_ne!(forward.raw(), &shuffled);
+        assert_eq!(forward, Obligations::new(&shuffled));
+    }
+
+    #[test]
+    fn test_same_settlement_in_any_order() {
+        let p = |from: &str, amount, to: &[&str]| {
+            Payment::new(
+                Person::new(from),
+                Money::new(amount),
+                &to.iter().map(|p| Person::new(*p)).collect::<Vec<_>>(),
+            )
+        };
+
+        let paid = [
+            p("A", 1200, &["B"]),
+            p("B", 1200, &["A", "C", "D"]),
+            p("C", 1200, &["A", "B", "D"]),
+            p("D", 1200, &["B", "C"]),
+            p("E", 900, &["A", "C"]),
+            p("A", 700, &["E", "D"]),
+        ];
+
+        let mut orders = Vec::new();
+        for reversed in [false, true] {
+            let mut order = paid.to_vec();
+            if reversed {
+                order.reverse();
+            }
+            for _ in 0..order.len() {
+                order.rotate_left(1);
+                orders.push(order.clone());
+            }
+        }
+
+        let expected = Payments::new(&paid).who_pays_whom().unwrap();
+        for order in &orders {
+            assert_eq!(
+                expected.raw(),
+                Payments::new(order).who_pays_whom().unwrap().raw()
+            );
+        }
+
+        // Nobody pays someone who also pays them back.
+        assert!(expected
+            .iter()
+            .all(|o| expected.between(&o.from, &o.to).len() == 1));
+    }
 }
@@ -0,0 +1,300 @@
+use crate::{Constraints, Money, Obligation, Obligations, Person};
+
+/// A way of sending money, such as `"cash"` or the name of a payment app.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct PaymentMethod(String);
+
+impl PaymentMethod {
+    #[inline(always)]
+    pub fn new(m: impl Into<String>) -> Self {
+        Self(m.into())
+    }
+
+    #[inline(always)]
+    pub const fn raw(&self) -> &String {
+        &self.0
+    }
+}
+
+/// Caps on single transfers and the payment methods people accept, applied by
+/// [`crate::Solver::with_limits`].
+///
+/// Someone who declares no payment method accepts any of them, while someone who declares an empty list
+/// accepts none and can't send or receive money directly. Non-positive caps are ignored.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TransferLimits {
+    global: Option<Money>,
+    people: Vec<(Person, Money)>,
+    methods: Vec<(PaymentMethod, Money)>,
+    accepts: Vec<(Person, Vec<PaymentMethod>)>,
+}
+
+impl TransferLimits {
+    #[inline(always)]
+    pub fn builder() -> TransferLimitsBuilder {
+        TransferLimitsBuilder::default()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.global.is_none()
+            && self.people.is_empty()
+            && self.methods.is_empty()
+            && self.accepts.is_empty()
+    }
+
+    /// The payment methods `person` declared, in order of preference, or `None` if they declared none.
+    #[inline(always)]
+    pub fn accepted_by(&self, person: &Person) -> Option<&[PaymentMethod]> {
+        self.accepts
+            .iter()
+            .find(|(p, _)| p == person)
+            .map(|(_, methods)| methods.as_slice())
+    }
+
+    /// The method `from` should use to pay `to`: among the ones both accept, the one with the highest cap,
+    /// favouring the payer's preference on ties. `Some(None)` means neither side declared any method.
+    pub fn method(&self, from: &Person, to: &Person) -> Option<Option<PaymentMethod>> {
+        let (from_methods, to_methods) = (self.accepted_by(from), self.accepted_by(to));
+
+        let common = match (from_methods, to_methods) {
+            (None, None) => return Some(None),
+            (Some(from_methods), None) => from_methods.to_vec(),
+            (None, Some(to_methods)) => to_methods.to_vec(),
+            (Some(from_methods), Some(to_methods)) => from_methods
+                .iter()
+                .filter(|m| to_methods.contains(m))
+                .cloned()
+                .collect(),
+        };
+
+        let cap = |method: &PaymentMethod| {
+            self.method_cap(method)
+                .map(|cap| cap.raw())
+                .unwrap_or(i32::MAX)
+        };
+
+        common
+            .iter()
+            .enumerate()
+            .max_by_key(|(i, method)| (cap(method), usize::MAX - i))
+            .map(|(_, method)| Some(method.clone()))
+    }
+
+    /// Adds a forbidden pair for everyone among `people` who doesn't share a payment method.
+    pub(crate) fn constrain(&self, constraints: &Constraints, people: &[Person]) -> Constraints {
+        let mut builder = constraints.to_builder();
+
+        for (i, a) in people.iter().enumerate() {
+            for b in &people[i + 1..] {
+                if self.method(a, b).is_none() {
+                    builder.forbid(a.clone(), b.clone());
+                }
+            }
+        }
+
+        builder.build()
+    }
+
+    /// Tags every obligation with a payment method, and splits the ones above their cap
+    /// into as few transfers as possible.
+    pub(crate) fn apply(&self, obligations: &Obligations) -> Obligations {
+        let mut limited = Obligations::builder();
+
+        for o in obligations.raw() {
+            let method = self.method(&o.from, &o.to).flatten();
+
+            let cap = [
+                self.global,
+                self.person_cap(&o.from),
+                self.person_cap(&o.to),
+                method.as_ref().and_then(|m| self.method_cap(m)),
+            ]
+            .into_iter()
+            .flatten()
+            .map(|cap| cap.raw())
+            .min()
+            .unwrap_or(i32::MAX);
+
+            let mut left = o.amount.raw();
+
+            while left > 0 {
+                let amount = left.min(cap);
+                left -= amount;
+
+                let mut obligation = Obligation::builder()
+                    .from(o.from.clone())
+                    .to(o.to.clone())
+                    .amount(Money::new(amount));
+
+                if let Some(method) = &method {
+                    obligation = obligation.method(method.clone());
+                }
+
+                limited.record(obligation.build());
+            }
+        }
+
+        limited.build()
+    }
+
+    #[inline(always)]
+    fn person_cap(&self, person: &Person) -> Option<Money> {
+        self.people
+            .iter()
+            .find(|(p, _)| p == person)
+            .map(|(_, cap)| *cap)
+    }
+
+    #[inline(always)]
+    fn method_cap(&self, method: &PaymentMethod) -> Option<Money> {
+        self.methods
+            .iter()
+            .find(|(m, _)| m == method)
+            .map(|(_, cap)| *cap)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TransferLimitsBuilder {
+    global: Option<Money>,
+    people: Vec<(Person, Money)>,
+    methods: Vec<(PaymentMethod, Money)>,
+    accepts: Vec<(Person, Vec<PaymentMethod>)>,
+}
+
+impl TransferLimitsBuilder {
+    /// No single transfer may exceed `cap`.
+    #[inline(always)]
+    pub fn global(&mut self, cap: Money) -> &mut Self {
+        self.global = Some(cap).filter(|cap| cap.raw() > 0);
+        self
+    }
+
+    /// No single transfer sent or received by `person` may exceed `cap`.
+    #[inline(always)]
+    pub fn person(&mut self, person: Person, cap: Money) -> &mut Self {
+        if cap.raw() > 0 {
+            self.people.push((person, cap));
+        }
+        self
+    }
+
+    /// No single transfer made with `method` may exceed `cap`.
+    #[inline(always)]
+    pub fn method(&mut self, method: PaymentMethod, cap: Money) -> &mut Self {
+        if cap.raw() > 0 {
+            self.methods.push((method, cap));
+        }
+        self
+    }
+
+    /// `person` can only send and receive money through `methods`, in order of preference.
+    #[inline(always)]
+    pub fn accepts(&mut self, person: Person, methods: &[PaymentMethod]) -> &mut Self {
+        self.accepts.push((person, methods.to_vec()));
+        self
+    }
+
+    #[inline(always)]
+    pub fn build(&mut self) -> TransferLimits {
+        TransferLimits {
+            global: self.global,
+            people: self.people.clone(),
+            methods: self.methods.clone(),
+            accepts: self.accepts.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Payment, Payments};
+
+    #[test]
+    fn test_caps_and_methods() {
+        let (a, b, c) = (Person::new("A"), Person::new("B"), Person::new("C"));
+        let (cash, app) = (PaymentMethod::new("cash"), PaymentMethod::new("app"));
+
+        let payments = Payments::builder()
+            .record(Payment::new(
+                a.clone(),
+                Money::new(90000),
+                &[a.clone(), b.clone(), c.clone()],
+            ))
+            .build();
+
+        // B and C owe A 300 each, but the app caps transfers at 250.
+        let limits = TransferLimits::builder()
+            .method(app.clone(), Money::new(25000))
+            .accepts(a.clone(), &[app.clone(), cash.clone()])
+            .accepts(b.clone(), std::slice::from_ref(&app))
+            .build();
+
+        let obligations = payments.solver().with_limits(limits).solve().unwrap();
+
+        let transfers = obligations
+            .raw()
+            .iter()
+            .map(|o| (o.from.raw().as_str(), o.amount.raw(), o.method().cloned()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                ("B", 5000, Some(app.clone())),
+                ("B", 25000, Some(app.clone())),
+                ("C", 30000, Some(cash.clone())),
+            ],
+            transfers
+        );
+
+        // C only takes cash, which B doesn't, so B's debt to C has to go through A.
+        let limits = TransferLimits::builder()
+            .global(Money::new(100000))
+            .accepts(b.clone(), std::slice::from_ref(&app))
+            .accepts(c.clone(), std::slice::from_ref(&cash))
+            .build();
+
+        let payments = Payments::builder()
+            .record(Payment::new(
+                c.clone(),
+                Money::new(2000),
+                std::slice::from_ref(&b),
+            ))
+            .record(Payment::new(
+                a.clone(),
+                Money::new(1000),
+                std::slice::from_ref(&b),
+            ))
+            .build();
+
+        let obligations = payments.solver().with_limits(limits).solve().unwrap();
+
+        assert_eq!(
+            vec![
+                Obligation::builder()
+                    .from(a.clone())
+                    .to(c)
+                    .amount(Money::new(1000))
+                    .method(cash)
+                    .build(),
+                Obligation::builder()
+                    .from(b)
+                    .to(a)
+                    .amount(Money::new(1500))
+                    .method(app)
+                    .build(),
+            ],
+            *obligations.raw()
+        );
+
+        // Declaring no method at all accepts any, declaring an empty list accepts none.
+        let (a, c) = (Person::new("A"), Person::new("C"));
+        let limits = TransferLimits::builder().accepts(c.clone(), &[]).build();
+        assert_eq!(Some(None), limits.method(&a, &Person::new("B")));
+        assert_eq!(None, limits.method(&a, &c));
+    }
+}
@@ -0,0 +1,233 @@
+use chrono::NaiveDate;
+
+use crate::{Money, Obligation, Obligations, Payments, Person};
+
+/// A time someone was part of a group, both ends included; `None` means it is open on that end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stay {
+    pub person: Person,
+    pub joined: Option<NaiveDate>,
+    pub left: Option<NaiveDate>,
+}
+
+impl Stay {
+    /// Whether the stay covers `date`; an undated payment only falls in a stay that hasn't ended.
+    #[inline(always)]
+    pub fn covers(&self, date: Option<NaiveDate>) -> bool {
+        match date {
+            Some(date) => {
+                self.joined.is_none_or(|joined| joined <= date)
+                    && self.left.is_none_or(|left| date <= left)
+            }
+            None => self.left.is_none(),
+        }
+    }
+}
+
+/// When people joined and left a group. Someone who never joined nor left is a member all along.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Membership(Vec<Stay>);
+
+impl Membership {
+    #[inline(always)]
+    pub const fn raw(&self) -> &Vec<Stay> {
+        &self.0
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// `person` is a member from `date` on, unless they already are.
+    pub fn join(&mut self, person: Person, date: NaiveDate) {
+        let open = self
+            .0
+            .iter()
+            .any(|stay| stay.person == person && stay.left.is_none());
+
+        if !open {
+            self.0.push(Stay {
+                person,
+                joined: Some(date),
+                left: None,
+            });
+        }
+    }
+
+    /// `person` is no longer a member after `date`.
+    pub fn leave(&mut self, person: Person, date: NaiveDate) {
+        let known = self.0.iter().any(|stay| stay.person == person);
+
+        match self
+            .0
+            .iter_mut()
+            .find(|stay| stay.person == person && stay.left.is_none())
+        {
+            Some(stay) => stay.left = Some(date),
+            None if !known => self.0.push(Stay {
+                person,
+                joined: None,
+                left: Some(date),
+            }),
+            None => {}
+        }
+    }
+
+    #[inline(always)]
+    pub fn is_member(&self, person: &Person, date: Option<NaiveDate>) -> bool {
+        let mut stays = self
+            .0
+            .iter()
+            .filter(|stay| &stay.person == person)
+            .peekable();
+
+        stays.peek().is_none() || stays.any(|stay| stay.covers(date))
+    }
+
+    /// Those of `people` who are members on `date`.
+    #[inline(always)]
+    pub fn members_on(&self, people: &[Person], date: Option<NaiveDate>) -> Vec<Person> {
+        people
+            .iter()
+            .filter(|person| self.is_member(person, date))
+            .cloned()
+            .collect()
+    }
+
+    /// Fills in who the payments made [`crate::PaymentBuilder::to_members`] are for:
+    /// those of `people` who are members on the payment's date.
+    pub fn resolve(&self, people: &[Person], payments: &Payments) -> Payments {
+        let resolved = payments
+            .raw()
+            .iter()
+            .map(|payment| match payment.is_to_members() {
+                true => payment.for_members(&self.members_on(people, payment.date())),
+                false => payment.clone(),
+            })
+            .collect::<Vec<_>>();
+
+        Payments::new(&resolved)
+    }
+}
+
+/// The transfers that bring `person`'s balance to zero, and no others: they pay the largest creditors first,
+/// or are paid by the largest debtors first.
+pub(crate) fn settle_out(person: &Person, balances: &[(Person, Money)]) -> Obligations {
+    let mut left = balances
+        .iter()
+        .find(|(p, _)| p == person)
+        .map_or(0, |(_, balance)| balance.raw());
+
+    let mut others = balances
+        .iter()
+        .filter(|(p, balance)| p != person && balance.raw().signum() == -left.signum())
+        .map(|(p, balance)| (p.clone(), balance.raw().abs()))
+        .collect::<Vec<_>>();
+    others.sort_by_key(|(_, balance)| -balance);
+
+    let mut obligations = Obligations::builder();
+
+    for (other, balance) in others {
+        if left == 0 {
+            break;
+        }
+
+        let amount = balance.min(left.abs());
+        let (from, to) = match left < 0 {
+            true => (person.clone(), other),
+            false => (other, person.clone()),
+        };
+
+        obligations.record(
+            Obligation::builder()
+                .from(from)
+                .to(to)
+                .amount(Money::new(amount))
+                .build(),
+        );
+        left -= left.signum() * amount;
+    }
+
+    obligations.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Group, Payment};
+
+    #[test]
+    fn test_joining_and_leaving() {
+        let (a, b, c) = (Person::new("A"), Person::new("B"), Person::new("C"));
+        let day = |d| NaiveDate::from_ymd_opt(2024, 5, d).unwrap();
+        let shared = |from: &Person, amount, d| {
+            Payment::builder()
+                .from(from.clone())
+                .amount(Money::new(amount))
+                .date(day(d))
+                .to_members()
+                .build()
+        };
+
+        let mut group = Group::new(&[a.clone(), b.clone()]);
+        group.join(c.clone(), day(3));
+
+        // Days 1 and 2 are split between A and B only, C shares from day 3 on.
+        group.record(shared(&a, 3000, 1));
+        group.record(shared(&b, 600, 2));
+        group.record(shared(&a, 3000, 3));
+        group.leave(b.clone(), day(4));
+        group.record(shared(&c, 1000, 5));
+
+        assert_eq!(
+            vec![
+                vec![a.clone(), b.clone()],
+                vec![a.clone(), b.clone()],
+                vec![a.clone(), b.clone(), c.clone()],
+                vec![a.clone(), c.clone()],
+            ],
+            group
+                .resolved_payments()
+                .raw()
+                .iter()
+                .map(|p| p.to().to_vec())
+                .collect::<Vec<_>>()
+        );
+
+        // B owes 15.00 for day 1 and 10.00 for day 3, and is owed 3.00 for day 2.
+        assert_eq!(
+            vec![(b.clone(), Money::new(-2200))],
+            group
+                .balances()
+                .into_iter()
+                .filter(|(p, _)| p == &b)
+                .collect::<Vec<_>>()
+        );
+
+        // The trace is of the same settlement, among the members.
+        assert_eq!(
+            group.who_pays_whom().unwrap(),
+            group.who_pays_whom_traced().unwrap().0
+        );
+
+        let out = group.settle_out(&b);
+        assert_eq!(Money::new(2200), out.total());
+        assert!(out.iter().all(|o| o.from == b));
+
+        // Outside a group, everyone in the payments is a member all along.
+        let mut balances = Payments::new(group.payments().raw()).balances();
+        balances.sort();
+        assert_eq!(
+            vec![
+                (a, Money::new(3467)),
+                (b, Money::new(-1933)),
+                (c, Money::new(-1534)),
+            ],
+            balances
+        );
+    }
+}
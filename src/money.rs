@@ -1,4 +1,18 @@
-#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd)]
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ParseMoneyError {
+    #[error("invalid amount {0:?}, expected something like 12 or 12.34")]
+    Invalid(String),
+
+    #[error("amount {0:?} is out of range")]
+    OutOfRange(String),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Money(i32);
 
 impl Money {
@@ -13,6 +27,54 @@ impl Money {
     }
 }
 
+/// Formats the amount with 2 decimals, such that `Money::new(1234)` is `12.34`.
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let cents = self.0.unsigned_abs();
+
+        f.pad(&format!("{sign}{}.{:02}", cents / 100, cents % 100))
+    }
+}
+
+/// Parses amounts written with up to 2 decimals, such that `"12.3"` is `Money::new(1230)`.
+impl FromStr for Money {
+    type Err = ParseMoneyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseMoneyError::Invalid(s.to_owned());
+        let out_of_range = || ParseMoneyError::OutOfRange(s.to_owned());
+
+        let (negative, unsigned) = match s.trim().strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.trim()),
+        };
+
+        let (units, cents) = match unsigned.split_once('.') {
+            Some((units, cents)) => (units, cents),
+            None => (unsigned, ""),
+        };
+
+        let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+
+        if units.is_empty() || cents.len() > 2 || !is_digits(units) || !is_digits(cents) {
+            return Err(invalid());
+        }
+
+        let units = units.parse::<i32>().map_err(|_| out_of_range())?;
+        let cents = format!("{cents:0<2}")
+            .parse::<i32>()
+            .map_err(|_| invalid())?;
+
+        let amount = units
+            .checked_mul(100)
+            .and_then(|units| units.checked_add(cents))
+            .ok_or_else(out_of_range)?;
+
+        Ok(Self(if negative { -amount } else { amount }))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -24,4 +86,29 @@ mod tests {
 
         assert_eq!(raw_amount, money.raw());
     }
+
+    #[test]
+    fn test_money_display_and_parse() {
+        assert_eq!("12.34", Money::new(1234).to_string());
+        assert_eq!("-0.05", Money::new(-5).to_string());
+        assert_eq!("0.00", Money::default().to_string());
+
+        assert_eq!(Ok(Money::new(1234)), "12.34".parse());
+        assert_eq!(Ok(Money::new(1230)), "12.3".parse());
+        assert_eq!(Ok(Money::new(1200)), "12".parse());
+        assert_eq!(Ok(Money::new(-5)), "-0.05".parse());
+
+        assert!(matches!(
+            "12.345".parse::<Money>(),
+            Err(ParseMoneyError::Invalid(_))
+        ));
+        assert!(matches!(
+            "abc".parse::<Money>(),
+            Err(ParseMoneyError::Invalid(_))
+        ));
+        assert!(matches!(
+            "99999999999".parse::<Money>(),
+            Err(ParseMoneyError::OutOfRange(_))
+        ));
+    }
 }
@@ -1,10 +1,24 @@
-use crate::{money::Money, person::Person};
+use crate::{limits::PaymentMethod, money::Money, payment::PaymentId, person::Person};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Obligations are compared by who pays whom, how much and how, whatever payments they come from.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Obligation {
     pub from: Person,
     pub to: Person,
     pub amount: Money,
+    /// A payment method both sides accept, when any was declared with [`crate::TransferLimits`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub(crate) method: Option<PaymentMethod>,
+    /// The payments this obligation comes from, and how much of it each one accounts for.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    pub(crate) sources: Vec<(PaymentId, Money)>,
 }
 
 impl Obligation {
@@ -12,6 +26,47 @@ impl Obligation {
     pub fn builder() -> ObligationBuilder {
         ObligationBuilder::default()
     }
+
+    /// A payment method both sides accept, when any was declared with [`crate::TransferLimits`].
+    #[inline(always)]
+    pub const fn method(&self) -> Option<&PaymentMethod> {
+        self.method.as_ref()
+    }
+
+    /// The payments this obligation comes from, and how much of it each one accounts for,
+    /// see [`crate::Payments::who_pays_whom_with_provenance`].
+    #[inline(always)]
+    pub fn sources(&self) -> &[(PaymentId, Money)] {
+        &self.sources
+    }
+
+    #[inline(always)]
+    fn key(&self) -> (&Person, &Person, Money, Option<&PaymentMethod>) {
+        (&self.from, &self.to, self.amount, self.method.as_ref())
+    }
+}
+
+impl PartialEq for Obligation {
+    #[inline(always)]
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Obligation {}
+
+impl PartialOrd for Obligation {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Obligation {
+    #[inline(always)]
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
 }
 
 #[derive(Default)]
@@ -19,12 +74,20 @@ pub struct ObligationBuilder {
     from: Person,
     to: Person,
     amount: Money,
+    method: Option<PaymentMethod>,
+    sources: Vec<(PaymentId, Money)>,
 }
 
 impl ObligationBuilder {
     #[inline(always)]
     pub const fn new(from: Person, to: Person, amount: Money) -> Self {
-        Self { from, to, amount }
+        Self {
+            from,
+            to,
+            amount,
+            method: None,
+            sources: Vec::new(),
+        }
     }
 
     #[inline(always)]
@@ -45,17 +108,35 @@ impl ObligationBuilder {
         self
     }
 
+    #[inline(always)]
+    pub fn method(mut self, method: PaymentMethod) -> Self {
+        self.method = Some(method);
+        self
+    }
+
+    /// Records that `amount` of this obligation comes from the payment `id`.
+    #[inline(always)]
+    pub fn source(mut self, id: PaymentId, amount: Money) -> Self {
+        self.sources.push((id, amount));
+        self
+    }
+
     #[inline(always)]
     pub fn build(self) -> Obligation {
         Obligation {
             from: self.from,
             to: self.to,
             amount: self.amount,
+            method: self.method,
+            sources: self.sources,
         }
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// Two `Obligations` are equal when they hold the same transfers, in any order.
+#[derive(Debug, Clone, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Obligations(Vec<Obligation>);
 
 impl Obligations {
@@ -73,6 +154,177 @@ impl Obligations {
     pub const fn raw(&self) -> &Vec<Obligation> {
         &self.0
     }
+
+    #[inline(always)]
+    pub fn iter(&self) -> std::slice::Iter<'_, Obligation> {
+        self.0.iter()
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Sum of every transfer.
+    #[inline(always)]
+    pub fn total(&self) -> Money {
+        Money::new(self.0.iter().map(|o| o.amount.raw()).sum())
+    }
+
+    #[inline(always)]
+    pub fn filter(&self, predicate: impl Fn(&Obligation) -> bool) -> Self {
+        Self(self.0.iter().filter(|o| predicate(o)).cloned().collect())
+    }
+
+    /// The transfers `person` has to make.
+    #[inline(always)]
+    pub fn owed_by(&self, person: &Person) -> Self {
+        self.filter(|o| &o.from == person)
+    }
+
+    /// The transfers `person` receives.
+    #[inline(always)]
+    pub fn owed_to(&self, person: &Person) -> Self {
+        self.filter(|o| &o.to == person)
+    }
+
+    /// The transfers between `a` and `b`, in either direction.
+    #[inline(always)]
+    pub fn between(&self, a: &Person, b: &Person) -> Self {
+        self.filter(|o| (&o.from == a && &o.to == b) || (&o.from == b && &o.to == a))
+    }
+
+    /// Adds `other` to these transfers, so that each pair of people is left with at most one transfer
+    /// in a single direction. A payment method is kept only when every merged transfer agrees on it.
+    pub fn merge(&self, other: &Self) -> Self {
+        let mut merged: Vec<Obligation> = Vec::new();
+
+        for o in self.0.iter().chain(&other.0) {
+            let existing = merged
+                .iter_mut()
+                .find(|m| (m.from == o.from && m.to == o.to) || (m.from == o.to && m.to == o.from));
+
+            match existing {
+                Some(m) => {
+                    let amount = match m.from == o.from {
+                        true => m.amount.raw() + o.amount.raw(),
+                        false => m.amount.raw() - o.amount.raw(),
+                    };
+
+                    if m.method != o.method {
+                        m.method = None;
+                    }
+
+                    if amount < 0 {
+                        std::mem::swap(&mut m.from, &mut m.to);
+                    }
+                    m.amount = Money::new(amount.abs());
+                }
+                None => merged.push(o.clone()),
+            }
+        }
+
+        merged.retain(|m| m.amount.raw() != 0);
+
+        let mut merged = Self(merged);
+        merged.sort();
+        merged
+    }
+
+    /// The transfers each debtor makes, debtors listed in the order they first appear.
+    #[inline(always)]
+    pub fn group_by_debtor(&self) -> Vec<(Person, Self)> {
+        self.group_by(|o| &o.from)
+    }
+
+    /// The transfers each creditor receives, creditors listed in the order they first appear.
+    #[inline(always)]
+    pub fn group_by_creditor(&self) -> Vec<(Person, Self)> {
+        self.group_by(|o| &o.to)
+    }
+
+    fn group_by(&self, key: impl Fn(&Obligation) -> &Person) -> Vec<(Person, Self)> {
+        let mut groups: Vec<(Person, Self)> = Vec::new();
+
+        for o in &self.0 {
+            match groups.iter_mut().find(|(p, _)| p == key(o)) {
+                Some((_, group)) => group.0.push(o.clone()),
+                None => groups.push((key(o).clone(), Self(vec![o.clone()]))),
+            }
+        }
+
+        groups
+    }
+
+    /// Puts the transfers in canonical order: by debtor, then by creditor, amount and payment method.
+    #[inline(always)]
+    pub fn sort(&mut self) {
+        self.0.sort();
+    }
+
+    /// Net balance of everyone involved, positive when they are owed money.
+    /// People are listed in the order they first appear.
+    pub fn balances(&self) -> Vec<(Person, Money)> {
+        let mut balances: Vec<(Person, Money)> = Vec::new();
+
+        let mut add = |person: &Person, amount: i32| {
+            match balances.iter_mut().find(|(p, _)| p == person) {
+                Some((_, balance)) => *balance = Money::new(balance.raw() + amount),
+                None => balances.push((person.clone(), Money::new(amount))),
+            };
+        };
+
+        for obligation in &self.0 {
+            add(&obligation.from, -obligation.amount.raw());
+            add(&obligation.to, obligation.amount.raw());
+        }
+
+        balances
+    }
+}
+
+impl IntoIterator for Obligations {
+    type Item = Obligation;
+    type IntoIter = std::vec::IntoIter<Obligation>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Obligations {
+    type Item = &'a Obligation;
+    type IntoIter = std::slice::Iter<'a, Obligation>;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
+
+impl FromIterator<Obligation> for Obligations {
+    #[inline(always)]
+    fn from_iter<I: IntoIterator<Item = Obligation>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl PartialEq for Obligations {
+    fn eq(&self, other: &Self) -> bool {
+        let sorted = |obligations: &Self| {
+            let mut obligations = obligations.0.clone();
+            obligations.sort();
+            obligations
+        };
+
+        sorted(self) == sorted(other)
+    }
 }
 
 #[derive(Debug, Default)]
@@ -120,4 +372,70 @@ mod tests {
         let obligations = Obligations::builder().record(obligation).build();
         assert_eq!(1, obligations.raw().len());
     }
+
+    #[test]
+    fn test_balances() {
+        let a = Person::new("A");
+        let b = Person::new("B");
+        let c = Person::new("C");
+
+        let obligations = Obligations::builder()
+            .record(ObligationBuilder::new(a.clone(), b.clone(), Money::new(10)).build())
+            .record(ObligationBuilder::new(b.clone(), c.clone(), Money::new(25)).build())
+            .build();
+
+        assert_eq!(
+            vec![
+                (a, Money::new(-10)),
+                (b, Money::new(-15)),
+                (c, Money::new(25))
+            ],
+            obligations.balances()
+        );
+    }
+
+    #[test]
+    fn test_queries() {
+        let (a, b, c) = (Person::new("A"), Person::new("B"), Person::new("C"));
+
+        let obligations = Obligations::builder()
+            .record(ObligationBuilder::new(a.clone(), b.clone(), Money::new(10)).build())
+            .record(ObligationBuilder::new(a.clone(), c.clone(), Money::new(20)).build())
+            .record(ObligationBuilder::new(c.clone(), b.clone(), Money::new(5)).build())
+            .build();
+
+        assert_eq!(Money::new(35), obligations.total());
+        assert_eq!(Money::new(30), obligations.owed_by(&a).total());
+        assert_eq!(2, obligations.owed_to(&b).len());
+        assert_eq!(1, obligations.between(&b, &c).len());
+        assert_eq!(
+            vec![a.clone(), c.clone()],
+            obligations
+                .group_by_debtor()
+                .into_iter()
+                .map(|(p, _)| p)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(Money::new(15), obligations.group_by_creditor()[0].1.total());
+
+        let back = Obligations::builder()
+            .record(ObligationBuilder::new(b.clone(), a.clone(), Money::new(25)).build())
+            .record(ObligationBuilder::new(b.clone(), c.clone(), Money::new(5)).build())
+            .build();
+
+        assert_eq!(
+            Obligations::new(&[
+                ObligationBuilder::new(a.clone(), c, Money::new(20)).build(),
+                ObligationBuilder::new(b, a, Money::new(15)).build(),
+            ]),
+            obligations.merge(&back)
+        );
+        assert_eq!(
+            3,
+            obligations
+                .into_iter()
+                .filter(|o| o.amount.raw() > 0)
+                .count()
+        );
+    }
 }
@@ -1,13 +1,139 @@
+use chrono::NaiveDate;
+
 use crate::money::Money;
 use crate::obligation::{Obligation, Obligations};
 use crate::person::Person;
-use crate::{Solver, SolverError};
+use crate::{Membership, Solver, SolverError, SolverTrace};
+
+/// Identifies a [`Payment`], so that the obligations it causes can be traced back to it.
+///
+/// Payments recorded without an id are given a generated one, which never equals an id given by hand
+/// and doesn't change when other payments are removed.
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct PaymentId(Id);
+
+#[derive(Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(untagged))]
+enum Id {
+    Named(String),
+    Generated(u64),
+}
+
+impl PaymentId {
+    #[inline(always)]
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(Id::Named(id.into()))
+    }
+
+    #[inline(always)]
+    pub(crate) const fn generated(n: u64) -> Self {
+        Self(Id::Generated(n))
+    }
+
+    /// The id given by hand, `None` for a generated one.
+    #[inline(always)]
+    pub fn name(&self) -> Option<&str> {
+        match &self.0 {
+            Id::Named(name) => Some(name),
+            Id::Generated(_) => None,
+        }
+    }
+
+    #[inline(always)]
+    pub const fn is_generated(&self) -> bool {
+        matches!(self.0, Id::Generated(_))
+    }
+}
+
+impl std::fmt::Display for PaymentId {
+    #[inline(always)]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Id::Named(name) => f.pad(name),
+            Id::Generated(n) => f.pad(&format!("#{n}")),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Payment {
     from: Person,
     amount: Money,
     to: Vec<Person>,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    split: Split,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    details: PaymentDetails,
+}
+
+/// How a [`Payment`] is split when its participants don't all take an equal share.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Split {
+    /// The most anyone takes of the payment.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    cap: Option<Money>,
+    /// The most some people take of the payment.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    caps: Vec<(Person, Money)>,
+    /// Whether the payment is split by the group's weights, see [`crate::Weights::resolve`].
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    weighted: bool,
+    /// How many parts of the payment each participant takes, instead of one each.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    weights: Vec<(Person, u32)>,
+}
+
+/// Optional information about a [`Payment`] that plays no part in splitting it.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct PaymentDetails {
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    id: Option<PaymentId>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    date: Option<NaiveDate>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    description: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    category: Option<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Vec::is_empty")
+    )]
+    tags: Vec<String>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "std::ops::Not::not")
+    )]
+    to_members: bool,
 }
 
 impl Payment {
@@ -17,6 +143,8 @@ impl Payment {
             from,
             amount,
             to: to.to_vec(),
+            split: Split::default(),
+            details: PaymentDetails::default(),
         }
     }
 
@@ -24,6 +152,193 @@ impl Payment {
     pub fn builder() -> PaymentBuilder {
         PaymentBuilder::default()
     }
+
+    #[inline(always)]
+    pub const fn from(&self) -> &Person {
+        &self.from
+    }
+
+    #[inline(always)]
+    pub const fn amount(&self) -> Money {
+        self.amount
+    }
+
+    #[inline(always)]
+    pub fn to(&self) -> &[Person] {
+        &self.to
+    }
+
+    #[inline(always)]
+    pub const fn id(&self) -> Option<&PaymentId> {
+        self.details.id.as_ref()
+    }
+
+    #[inline(always)]
+    pub const fn date(&self) -> Option<NaiveDate> {
+        self.details.date
+    }
+
+    #[inline(always)]
+    pub fn description(&self) -> Option<&str> {
+        self.details.description.as_deref()
+    }
+
+    #[inline(always)]
+    pub fn category(&self) -> Option<&str> {
+        self.details.category.as_deref()
+    }
+
+    #[inline(always)]
+    pub fn tags(&self) -> &[String] {
+        &self.details.tags
+    }
+
+    /// Whether the payment is for whoever is a member of the group on its date,
+    /// until [`crate::Membership::resolve`] fills in who that is.
+    #[inline(always)]
+    pub const fn is_to_members(&self) -> bool {
+        self.details.to_members
+    }
+
+    /// Everyone the payment is split among: the people it was for, and the payer.
+    #[inline(always)]
+    pub fn participants(&self) -> Vec<Person> {
+        let mut participants = self.to.clone();
+
+        if !participants.contains(&self.from) {
+            participants.push(self.from.clone());
+        }
+
+        participants
+    }
+
+    /// Whether the payment is split by the group's weights, see [`crate::Weights::resolve`].
+    #[inline(always)]
+    pub const fn is_weighted(&self) -> bool {
+        self.split.weighted
+    }
+
+    /// How many parts of the payment each participant takes, when they don't take one each.
+    #[inline(always)]
+    pub fn weights(&self) -> &[(Person, u32)] {
+        &self.split.weights
+    }
+
+    /// How many parts of the payment `person` takes: one each, unless weights were given.
+    #[inline(always)]
+    pub fn weight_of(&self, person: &Person) -> u32 {
+        match self.split.weights.is_empty() {
+            true => 1,
+            false => self
+                .split
+                .weights
+                .iter()
+                .find(|(p, _)| p == person)
+                .map_or(0, |(_, weight)| *weight),
+        }
+    }
+
+    /// The most `person` takes of the payment, if they are capped.
+    #[inline(always)]
+    pub fn cap_of(&self, person: &Person) -> Option<Money> {
+        let cap = self
+            .split
+            .caps
+            .iter()
+            .find(|(p, _)| p == person)
+            .map(|(_, cap)| *cap);
+
+        match (cap, self.split.cap) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    /// Each participant's part of the payment, rounded down to the cent.
+    ///
+    /// Parts are equal, or in proportion to the participants' weights, except that nobody takes more than their cap:
+    /// what goes over is split among the others, and whatever is left when everyone is capped is on the payer,
+    /// see [`Payment::unallocated`].
+    #[inline(always)]
+    pub fn shares(&self) -> Vec<(Person, Money)> {
+        self.split().0
+    }
+
+    /// What nobody takes of the payment because everyone is capped, or has no weight, and the payer bears alone.
+    #[inline(always)]
+    pub fn unallocated(&self) -> Money {
+        self.split().1
+    }
+
+    fn split(&self) -> (Vec<(Person, Money)>, Money) {
+        let participants = self.participants();
+        let weights = participants
+            .iter()
+            .map(|p| i64::from(self.weight_of(p)))
+            .collect::<Vec<_>>();
+        let mut shares = vec![None; participants.len()];
+        let mut left = self.amount.raw();
+        let collect = |shares: &[Option<i32>]| {
+            participants
+                .iter()
+                .cloned()
+                .zip(shares.iter().copied())
+                .map(|(person, share)| (person, Money::new(share.unwrap_or_default())))
+                .collect()
+        };
+
+        loop {
+            let open = (0..participants.len()).filter(|i| shares[*i].is_none());
+            let total = open.clone().map(|i| weights[i]).sum::<i64>();
+            if total == 0 {
+                return (collect(&shares), Money::new(left));
+            }
+
+            let part = |i: usize| (i64::from(left) * weights[i] / total) as i32;
+            let capped = open
+                .clone()
+                .filter_map(|i| {
+                    self.cap_of(&participants[i])
+                        .filter(|cap| cap.raw() < part(i))
+                        .map(|cap| (i, cap.raw()))
+                })
+                .collect::<Vec<_>>();
+
+            if capped.is_empty() {
+                let parts = open.map(|i| (i, part(i))).collect::<Vec<_>>();
+                for (i, part) in parts {
+                    shares[i] = Some(part);
+                }
+                return (collect(&shares), Money::new(0));
+            }
+
+            for (i, cap) in capped {
+                shares[i] = Some(cap);
+                left -= cap;
+            }
+        }
+    }
+
+    /// The payment made [`PaymentBuilder::to_members`], now for `members`.
+    #[inline(always)]
+    pub(crate) fn for_members(&self, members: &[Person]) -> Self {
+        let mut payment = self.clone();
+        payment.to = members.to_vec();
+        payment.details.to_members = false;
+        payment
+    }
+
+    /// A builder starting from this payment, to change some of it.
+    #[inline(always)]
+    pub fn to_builder(&self) -> PaymentBuilder {
+        PaymentBuilder {
+            from: self.from.clone(),
+            amount: self.amount,
+            to: self.to.clone(),
+            split: self.split.clone(),
+            details: self.details.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -31,6 +346,8 @@ pub struct PaymentBuilder {
     from: Person,
     amount: Money,
     to: Vec<Person>,
+    split: Split,
+    details: PaymentDetails,
 }
 
 impl PaymentBuilder {
@@ -40,6 +357,8 @@ impl PaymentBuilder {
             from,
             amount,
             to: to.to_vec(),
+            split: Split::default(),
+            details: PaymentDetails::default(),
         }
     }
 
@@ -61,12 +380,85 @@ impl PaymentBuilder {
         self
     }
 
+    /// Nobody takes more than `cap` of the payment, such as paying at most $200 toward the cabin.
+    #[inline(always)]
+    pub const fn cap(mut self, cap: Money) -> Self {
+        self.split.cap = Some(cap);
+        self
+    }
+
+    /// The payment is split by the group's weights, such as incomes, see [`crate::Weights::resolve`].
+    ///
+    /// Only a [`crate::Group`] or a [`crate::Ledger::with_weights`] knows the weights: plain [`Payments`],
+    /// and a [`Solver`] built from them, split the payment equally.
+    #[inline(always)]
+    pub const fn weighted(mut self) -> Self {
+        self.split.weighted = true;
+        self
+    }
+
+    /// `person` takes `weight` parts of the payment, and those without a weight none of it.
+    #[inline(always)]
+    pub fn weight(mut self, person: Person, weight: u32) -> Self {
+        self.split.weights.retain(|(p, _)| p != &person);
+        self.split.weights.push((person, weight));
+        self
+    }
+
+    /// `person` takes no more than `cap` of the payment.
+    #[inline(always)]
+    pub fn cap_for(mut self, person: Person, cap: Money) -> Self {
+        self.split.caps.retain(|(p, _)| p != &person);
+        self.split.caps.push((person, cap));
+        self
+    }
+
+    #[inline(always)]
+    pub fn id(mut self, id: PaymentId) -> Self {
+        self.details.id = Some(id);
+        self
+    }
+
+    #[inline(always)]
+    pub const fn date(mut self, date: NaiveDate) -> Self {
+        self.details.date = Some(date);
+        self
+    }
+
+    #[inline(always)]
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.details.description = Some(description.into());
+        self
+    }
+
+    #[inline(always)]
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.details.category = Some(category.into());
+        self
+    }
+
+    #[inline(always)]
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.details.tags.push(tag.into());
+        self
+    }
+
+    /// The payment is for whoever is a member of the group on its date, instead of the people in `to`,
+    /// see [`crate::Membership::resolve`].
+    #[inline(always)]
+    pub const fn to_members(mut self) -> Self {
+        self.details.to_members = true;
+        self
+    }
+
     #[inline(always)]
     pub fn build(self) -> Payment {
         Payment {
             from: self.from,
             to: self.to,
             amount: self.amount,
+            split: self.split,
+            details: self.details,
         }
     }
 }
@@ -96,9 +488,26 @@ impl PaymentsBuilder {
     }
 }
 
-#[derive(Debug)]
+/// Every payment in it has an id: the one it was given, or a generated one when it is recorded.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "Vec<Payment>", into = "Vec<Payment>"))]
 pub struct Payments(Vec<Payment>);
 
+impl From<Vec<Payment>> for Payments {
+    #[inline(always)]
+    fn from(payments: Vec<Payment>) -> Self {
+        Self::new(&payments)
+    }
+}
+
+impl From<Payments> for Vec<Payment> {
+    #[inline(always)]
+    fn from(payments: Payments) -> Self {
+        payments.0
+    }
+}
+
 impl Payments {
     #[inline(always)]
     pub fn builder() -> PaymentsBuilder {
@@ -107,35 +516,118 @@ impl Payments {
 
     #[inline(always)]
     pub fn new(payments: &[Payment]) -> Self {
-        Self(payments.to_vec())
+        let mut new = Self(Vec::with_capacity(payments.len()));
+
+        for payment in payments {
+            new.record(payment.clone());
+        }
+
+        new
     }
 
     #[inline(always)]
-    pub(crate) fn each_pays(&self) -> Obligations {
-        let mut obligations = Obligations::builder();
+    pub const fn raw(&self) -> &Vec<Payment> {
+        &self.0
+    }
+
+    /// Records `payment`, giving it a generated id if it has none.
+    #[inline(always)]
+    pub fn record(&mut self, mut payment: Payment) {
+        if payment.details.id.is_none() {
+            payment.details.id = Some(self.next_id());
+        }
+
+        self.0.push(payment);
+    }
+
+    /// Replaces the payment at `index`, returning the previous one if there was any.
+    /// The new payment keeps the previous one's id unless it has its own.
+    #[inline(always)]
+    pub fn replace(&mut self, index: usize, mut payment: Payment) -> Option<Payment> {
+        self.0.get_mut(index).map(|previous| {
+            if payment.details.id.is_none() {
+                payment.details.id = previous.details.id.clone();
+            }
+
+            std::mem::replace(previous, payment)
+        })
+    }
+
+    #[inline(always)]
+    pub fn remove(&mut self, index: usize) -> Option<Payment> {
+        (index < self.0.len()).then(|| self.0.remove(index))
+    }
+
+    /// The id of the payment at `index`.
+    #[inline(always)]
+    pub fn id_of(&self, index: usize) -> Option<PaymentId> {
+        self.0.get(index).and_then(|payment| payment.id().cloned())
+    }
+
+    /// The next generated id, after every one generated so far.
+    #[inline(always)]
+    fn next_id(&self) -> PaymentId {
+        let last = self
+            .0
+            .iter()
+            .filter_map(|payment| match payment.details.id {
+                Some(PaymentId(Id::Generated(n))) => Some(n),
+                _ => None,
+            })
+            .max();
+
+        PaymentId::generated(last.map_or(1, |n| n + 1))
+    }
+
+    /// Everyone who paid or was paid for, in the order they first appear.
+    pub fn people(&self) -> Vec<Person> {
+        let mut people: Vec<Person> = Vec::new();
 
         for payment in &self.0 {
-            let to = &payment.to;
+            for person in std::iter::once(&payment.from).chain(&payment.to) {
+                if !people.contains(person) {
+                    people.push(person.clone());
+                }
+            }
+        }
 
-            let included = match to.iter().find(|person| *person == &payment.from) {
-                Some(_) => 0,
-                None => 1,
-            };
+        people
+    }
+
+    /// Net balance of everyone involved, positive when they are owed money.
+    #[inline(always)]
+    pub fn balances(&self) -> Vec<(Person, Money)> {
+        self.each_pays().balances()
+    }
+
+    /// What each participant owes the payer of each payment.
+    ///
+    /// Payments made [`PaymentBuilder::to_members`] that no [`crate::Group`] resolved are split among
+    /// everyone in [`Payments::people`], as if they had all been members all along, and the ones made
+    /// [`PaymentBuilder::weighted`] that no weights resolved are split equally.
+    pub(crate) fn each_pays(&self) -> Obligations {
+        if self.0.iter().any(Payment::is_to_members) {
+            return Membership::default()
+                .resolve(&self.people(), self)
+                .each_pays();
+        }
 
-            let included = to.len() + included;
+        let mut obligations = Obligations::builder();
 
-            let total = f64::from(payment.amount.raw() / included as i32).ceil() as i32;
+        for payment in &self.0 {
+            let id = payment.id().cloned().unwrap_or(PaymentId::generated(0));
 
-            for debtor in to {
-                if debtor == &payment.from || total == 0 {
+            for (debtor, share) in payment.shares() {
+                if debtor == payment.from || share.raw() == 0 {
                     continue;
                 }
 
                 obligations.record(
                     Obligation::builder()
-                        .from(debtor.clone())
+                        .from(debtor)
                         .to(payment.from.clone())
-                        .amount(Money::new(total))
+                        .amount(share)
+                        .source(id.clone(), share)
                         .build(),
                 );
             }
@@ -144,13 +636,31 @@ impl Payments {
         obligations.build()
     }
 
+    /// A solver over what everyone owes, to be configured before solving.
+    #[inline(always)]
+    pub fn solver(&self) -> Solver {
+        Solver::from(self.each_pays())
+    }
+
     #[inline(always)]
     pub fn who_pays_whom(&self) -> Result<Obligations, SolverError> {
         Solver::from(self.each_pays()).solve()
     }
+
+    /// Same as [`Payments::who_pays_whom`], with the payments behind each transfer in its `sources`.
+    #[inline(always)]
+    pub fn who_pays_whom_with_provenance(&self) -> Result<Obligations, SolverError> {
+        Solver::from(self.each_pays()).solve_with_provenance()
+    }
+
+    #[inline(always)]
+    pub fn who_pays_whom_traced(&self) -> Result<(Obligations, SolverTrace), SolverError> {
+        Solver::from(self.each_pays()).solve_traced()
+    }
 }
 
 #[cfg(test)]
+#[allow(clippy::cloned_ref_to_slice_refs)]
 mod tests {
     use super::*;
 
@@ -194,4 +704,73 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_details_are_traced_through_each_pays() {
+        let (a, b) = (Person::new("A"), Person::new("B"));
+
+        let dinner = Payment::builder()
+            .from(a.clone())
+            .to(&[a.clone(), b.clone()])
+            .amount(Money::new(3700))
+            .id(PaymentId::new("dinner"))
+            .date(NaiveDate::from_ymd_opt(2024, 5, 17).unwrap())
+            .description("Dinner at the harbour")
+            .category("food")
+            .tag("trip")
+            .build();
+
+        assert_eq!(Some("food"), dinner.category());
+        assert_eq!(&["trip".to_owned()], dinner.tags());
+
+        let payments = Payments::new(&[
+            Payment::new(b.clone(), Money::new(1000), std::slice::from_ref(&a)),
+            dinner,
+        ]);
+
+        let sources = payments
+            .each_pays()
+            .into_iter()
+            .map(|o| o.sources().to_vec())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                vec![(PaymentId::generated(1), Money::new(500))],
+                vec![(PaymentId::new("dinner"), Money::new(1850))],
+            ],
+            sources
+        );
+    }
+
+    #[test]
+    fn test_generated_ids_are_stable() {
+        let (a, b) = (Person::new("A"), Person::new("B"));
+        let paid = |amount| Payment::new(a.clone(), Money::new(amount), std::slice::from_ref(&b));
+
+        let mut payments = Payments::new(&[paid(100), paid(200)]);
+        payments.record(
+            Payment::builder()
+                .from(a.clone())
+                .amount(Money::new(300))
+                .id(PaymentId::new("1"))
+                .build(),
+        );
+        payments.record(paid(400));
+
+        // A hand-picked "1" is not the generated #1, and removing a payment leaves the other ids alone.
+        assert_ne!(payments.id_of(0), payments.id_of(2));
+        payments.remove(0);
+        assert_eq!(
+            vec!["#2", "1", "#3"],
+            (0..3)
+                .map(|i| payments.id_of(i).unwrap().to_string())
+                .collect::<Vec<_>>()
+        );
+
+        payments.replace(0, paid(250));
+        payments.record(paid(500));
+        assert_eq!(Some(PaymentId::generated(2)), payments.id_of(0));
+        assert_eq!(Some(PaymentId::generated(4)), payments.id_of(3));
+    }
 }
@@ -1,4 +1,8 @@
-#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+use std::fmt;
+
+#[derive(Debug, Default, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
 pub struct Person(String);
 
 impl Person {
@@ -13,6 +17,26 @@ impl Person {
     }
 }
 
+impl From<&str> for Person {
+    #[inline(always)]
+    fn from(p: &str) -> Self {
+        Self::new(p)
+    }
+}
+
+impl From<String> for Person {
+    #[inline(always)]
+    fn from(p: String) -> Self {
+        Self::new(p)
+    }
+}
+
+impl fmt::Display for Person {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(&self.0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
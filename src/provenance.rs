@@ -0,0 +1,202 @@
+use petgraph::graph::EdgeIndex;
+
+use crate::{Money, PaymentId};
+
+/// Payment ids and the signed amount each one accounts for.
+pub(crate) type Sources = Vec<(PaymentId, i32)>;
+
+/// The payments behind every edge of the solver's graph, indexed like the edges themselves
+/// and kept up to date by the passes, see [`crate::Solver::solve_with_provenance`].
+///
+/// Amounts are signed: a payment that made `to` owe `from` shows up as a negative amount in `from -> to`,
+/// so that the amounts behind an edge always add up to its weight.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Provenance(Vec<Sources>);
+
+impl Provenance {
+    #[inline(always)]
+    pub(crate) fn get(&self, edge: EdgeIndex) -> Sources {
+        self.0.get(edge.index()).cloned().unwrap_or_default()
+    }
+
+    /// Sources for an edge just added to the graph.
+    #[inline(always)]
+    pub(crate) fn push(&mut self, sources: Sources) {
+        self.0.push(sources);
+    }
+
+    #[inline(always)]
+    pub(crate) fn add(&mut self, edge: EdgeIndex, sources: &Sources) {
+        if let Some(existing) = self.0.get_mut(edge.index()) {
+            *existing = combine(existing, sources, 1);
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn clear(&mut self, edge: EdgeIndex) {
+        if let Some(existing) = self.0.get_mut(edge.index()) {
+            existing.clear();
+        }
+    }
+
+    /// Mirrors [`petgraph::Graph::remove_edge`], which moves the last edge into the removed one's index.
+    #[inline(always)]
+    pub(crate) fn remove(&mut self, edge: EdgeIndex) {
+        if edge.index() < self.0.len() {
+            self.0.swap_remove(edge.index());
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn sources(&self, edge: EdgeIndex) -> Vec<(PaymentId, Money)> {
+        self.get(edge)
+            .into_iter()
+            .map(|(id, amount)| (id, Money::new(amount)))
+            .collect()
+    }
+}
+
+/// `a` plus `b` times `sign`, adding up the amounts of the same payment.
+pub(crate) fn combine(a: &Sources, b: &Sources, sign: i32) -> Sources {
+    let mut combined = a.clone();
+
+    for (id, amount) in b {
+        match combined.iter_mut().find(|(i, _)| i == id) {
+            Some((_, total)) => *total += sign * amount,
+            None => combined.push((id.clone(), sign * amount)),
+        }
+    }
+
+    combined.retain(|(_, amount)| *amount != 0);
+    combined
+}
+
+/// `sources` scaled so they add up to `total`, rounding towards zero and leaving the remainder
+/// to the first payment.
+pub(crate) fn scale(sources: &Sources, total: i32) -> Sources {
+    let sum = sources
+        .iter()
+        .map(|(_, amount)| i64::from(*amount))
+        .sum::<i64>();
+    if sum == 0 {
+        return Sources::new();
+    }
+
+    let mut scaled = sources
+        .iter()
+        .map(|(id, amount)| {
+            (
+                id.clone(),
+                (i64::from(*amount) * i64::from(total) / sum) as i32,
+            )
+        })
+        .collect::<Sources>();
+
+    let remainder = total - scaled.iter().map(|(_, amount)| amount).sum::<i32>();
+    if let Some((_, first)) = scaled.first_mut() {
+        *first += remainder;
+    }
+
+    scaled.retain(|(_, amount)| *amount != 0);
+    scaled
+}
+#[cfg(test)]
+mod tests {
+    use crate::{Money, Payment, PaymentId, Payments, Person};
+
+    #[test]
+    fn test_sources_add_up_to_every_transfer() {
+        let everyone = ["A", "B", "C", "H"].map(Person::new);
+        let [a, b, c, h] = everyone.clone();
+
+        let payments = Payments::builder()
+            .record(
+                Payment::builder()
+                    .from(a.clone())
+                    .amount(Money::new(2000))
+                    .to(&[b.clone(), c.clone(), h.clone()])
+                    .id(PaymentId::new("hotel"))
+                    .build(),
+            )
+            .record(
+                Payment::builder()
+                    .from(c.clone())
+                    .amount(Money::new(500))
+                    .to(std::slice::from_ref(&h))
+                    .id(PaymentId::new("taxi"))
+                    .build(),
+            )
+            .record(
+                Payment::builder()
+                    .from(b.clone())
+                    .amount(Money::new(600))
+                    .to(&[c.clone(), h.clone()])
+                    .id(PaymentId::new("dinner"))
+                    .build(),
+            )
+            .build();
+
+        let obligations = payments.who_pays_whom_with_provenance().unwrap();
+
+        let without_sources = |o: &crate::Obligation| (o.from.clone(), o.to.clone(), o.amount);
+        assert_eq!(
+            payments
+                .who_pays_whom()
+                .unwrap()
+                .iter()
+                .map(without_sources)
+                .collect::<Vec<_>>(),
+            obligations.iter().map(without_sources).collect::<Vec<_>>()
+        );
+
+        for o in &obligations {
+            let total: i32 = o.sources().iter().map(|(_, amount)| amount.raw()).sum();
+            assert_eq!(o.amount.raw(), total, "{o:?}");
+        }
+
+        // H owed B for dinner, but B owed A for the hotel, so H's dinner share goes to A now.
+        let h_pays_a = obligations.between(&h, &a).into_iter().next().unwrap();
+        assert!(h_pays_a
+            .sources()
+            .iter()
+            .any(|(id, _)| id == &PaymentId::new("dinner")));
+
+        // A owes B for the taxi and B owes C as much for the hotel, so A pays C, for both.
+        let payments = Payments::builder()
+            .record(
+                Payment::builder()
+                    .from(b.clone())
+                    .amount(Money::new(2000))
+                    .to(std::slice::from_ref(&a))
+                    .id(PaymentId::new("taxi"))
+                    .build(),
+            )
+            .record(
+                Payment::builder()
+                    .from(c.clone())
+                    .amount(Money::new(2000))
+                    .to(std::slice::from_ref(&b))
+                    .id(PaymentId::new("hotel"))
+                    .build(),
+            )
+            .build();
+
+        let obligations = payments.who_pays_whom_with_provenance().unwrap();
+        assert_eq!(1, obligations.len());
+        assert_eq!(
+            (&a, &c, Money::new(1000)),
+            (
+                &obligations.raw()[0].from,
+                &obligations.raw()[0].to,
+                obligations.raw()[0].amount
+            )
+        );
+        assert_eq!(
+            &[
+                (PaymentId::new("taxi"), Money::new(500)),
+                (PaymentId::new("hotel"), Money::new(500))
+            ],
+            obligations.raw()[0].sources()
+        );
+    }
+}
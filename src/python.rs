@@ -0,0 +1,270 @@
+//! Python bindings, built with `maturin build` (see `pyproject.toml`).
+
+use pyo3::{create_exception, exceptions::PyException, exceptions::PyValueError, prelude::*};
+
+use crate::{Money, Obligation, Obligations, Payment, Payments, Person};
+
+create_exception!(
+    pagat,
+    SolverError,
+    PyException,
+    "Raised when the solver cannot settle the payments."
+);
+
+impl From<crate::SolverError> for PyErr {
+    fn from(err: crate::SolverError) -> Self {
+        SolverError::new_err(err.to_string())
+    }
+}
+
+#[pyclass(name = "Person", module = "pagat", frozen, eq, hash)]
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct PyPerson(Person);
+
+#[pymethods]
+impl PyPerson {
+    #[new]
+    fn new(name: String) -> Self {
+        Self(Person::new(name))
+    }
+
+    #[getter]
+    fn name(&self) -> &str {
+        self.0.raw()
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Person({:?})", self.0.raw())
+    }
+}
+
+/// An amount in cents, such that `Money(100)` is $1.00.
+#[pyclass(name = "Money", module = "pagat", frozen, eq, ord)]
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+struct PyMoney(Money);
+
+#[pymethods]
+impl PyMoney {
+    #[new]
+    fn new(cents: i32) -> Self {
+        Self(Money::new(cents))
+    }
+
+    /// Parses amounts written with up to 2 decimals, such as `"12.34"`.
+    #[staticmethod]
+    fn parse(amount: &str) -> PyResult<Self> {
+        amount
+            .parse()
+            .map(Self)
+            .map_err(|err: crate::ParseMoneyError| PyValueError::new_err(err.to_string()))
+    }
+
+    #[getter]
+    fn cents(&self) -> i32 {
+        self.0.raw()
+    }
+
+    fn __int__(&self) -> i32 {
+        self.0.raw()
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Money({})", self.0.raw())
+    }
+}
+
+#[pyclass(name = "Payment", module = "pagat", frozen)]
+#[derive(Clone)]
+struct PyPayment(Payment);
+
+#[pymethods]
+impl PyPayment {
+    #[new]
+    fn new(from_: PyPerson, amount: PyMoney, to: Vec<PyPerson>) -> Self {
+        let to = to.into_iter().map(|p| p.0).collect::<Vec<_>>();
+        Self(Payment::new(from_.0, amount.0, &to))
+    }
+
+    #[getter]
+    fn from_(&self) -> PyPerson {
+        PyPerson(self.0.from().clone())
+    }
+
+    #[getter]
+    fn amount(&self) -> PyMoney {
+        PyMoney(self.0.amount())
+    }
+
+    #[getter]
+    fn to(&self) -> Vec<PyPerson> {
+        self.0.to().iter().cloned().map(PyPerson).collect()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Payment(Person({:?}), Money({}), {:?})",
+            self.0.from().raw(),
+            self.0.amount().raw(),
+            self.0.to().iter().map(Person::raw).collect::<Vec<_>>()
+        )
+    }
+}
+
+#[pyclass(name = "Payments", module = "pagat")]
+struct PyPayments(Payments);
+
+#[pymethods]
+impl PyPayments {
+    #[new]
+    #[pyo3(signature = (payments = Vec::new()))]
+    fn new(payments: Vec<PyPayment>) -> Self {
+        let payments = payments.into_iter().map(|p| p.0).collect::<Vec<_>>();
+        Self(Payments::new(&payments))
+    }
+
+    fn record(&mut self, payment: PyPayment) {
+        self.0.record(payment.0);
+    }
+
+    fn balances(&self) -> Vec<(PyPerson, PyMoney)> {
+        self.0
+            .balances()
+            .into_iter()
+            .map(|(person, balance)| (PyPerson(person), PyMoney(balance)))
+            .collect()
+    }
+
+    fn who_pays_whom(&self) -> PyResult<PyObligations> {
+        Ok(PyObligations(self.0.who_pays_whom()?))
+    }
+
+    fn __len__(&self) -> usize {
+        self.0.raw().len()
+    }
+}
+
+#[pyclass(name = "Obligation", module = "pagat", frozen)]
+#[derive(Clone)]
+struct PyObligation(Obligation);
+
+#[pymethods]
+impl PyObligation {
+    #[getter]
+    fn from_(&self) -> PyPerson {
+        PyPerson(self.0.from.clone())
+    }
+
+    #[getter]
+    fn to(&self) -> PyPerson {
+        PyPerson(self.0.to.clone())
+    }
+
+    #[getter]
+    fn amount(&self) -> PyMoney {
+        PyMoney(self.0.amount)
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Obligation({:?} -> {:?}: {})",
+            self.0.from.raw(),
+            self.0.to.raw(),
+            self.0.amount
+        )
+    }
+}
+
+#[pyclass(name = "Obligations", module = "pagat", frozen)]
+struct PyObligations(Obligations);
+
+#[pymethods]
+impl PyObligations {
+    fn __len__(&self) -> usize {
+        self.0.raw().len()
+    }
+
+    fn __getitem__(&self, index: usize) -> PyResult<PyObligation> {
+        self.0
+            .raw()
+            .get(index)
+            .cloned()
+            .map(PyObligation)
+            .ok_or_else(|| pyo3::exceptions::PyIndexError::new_err(index))
+    }
+
+    fn to_list(&self) -> Vec<PyObligation> {
+        self.0.raw().iter().cloned().map(PyObligation).collect()
+    }
+
+    /// `(from, to, cents)` tuples, ready for `pandas.DataFrame.from_records`.
+    fn to_records(&self) -> Vec<(String, String, i32)> {
+        self.0
+            .raw()
+            .iter()
+            .map(|o| (o.from.raw().clone(), o.to.raw().clone(), o.amount.raw()))
+            .collect()
+    }
+}
+
+#[pymodule]
+fn pagat(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPerson>()?;
+    m.add_class::<PyMoney>()?;
+    m.add_class::<PyPayment>()?;
+    m.add_class::<PyPayments>()?;
+    m.add_class::<PyObligation>()?;
+    m.add_class::<PyObligations>()?;
+    m.add("SolverError", m.py().get_type::<SolverError>())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+    use std::ffi::CString;
+
+    #[test]
+    fn test_python_module() {
+        pyo3::prepare_freethreaded_python();
+
+        Python::with_gil(|py| {
+            let locals = PyDict::new(py);
+            locals
+                .set_item("pagat", pyo3::wrap_pymodule!(pagat)(py))
+                .unwrap();
+
+            let script = CString::new(
+                r#"
+a, b, c = pagat.Person("A"), pagat.Person("B"), pagat.Person("C")
+payments = pagat.Payments([pagat.Payment(a, pagat.Money.parse("30"), [a, b, c])])
+
+obligations = payments.who_pays_whom()
+assert len(obligations) == 2
+assert obligations.to_records() == [("B", "A", 1000), ("C", "A", 1000)]
+assert obligations[0].from_ == b and obligations[0].amount == pagat.Money(1000)
+assert str(obligations[1].amount) == "10.00"
+
+try:
+    pagat.Money.parse("abc")
+    raise AssertionError("expected a ValueError")
+except ValueError:
+    pass
+
+assert issubclass(pagat.SolverError, Exception)
+"#,
+            )
+            .unwrap();
+
+            py.run(&script, None, Some(&locals)).unwrap();
+        });
+    }
+}
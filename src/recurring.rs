@@ -0,0 +1,212 @@
+use chrono::{Days, Months, NaiveDate};
+
+use crate::{Payment, PaymentId, Payments, Person};
+
+/// How often a [`RecurringPayment`] happens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Frequency {
+    Weekly,
+    /// On the same day every month, or the last day of shorter months.
+    Monthly,
+    Yearly,
+}
+
+/// A payment made again and again, such as the rent, expanded into concrete [`Payment`]s
+/// with [`RecurringPayment::expand`] or [`Payments::record_recurring`].
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecurringPayment {
+    template: Payment,
+    frequency: Frequency,
+    start: NaiveDate,
+    end: Option<NaiveDate>,
+    /// Who the payment is for from a given date on, sorted by date.
+    participants: Vec<(NaiveDate, Vec<Person>)>,
+}
+
+impl RecurringPayment {
+    /// Repeats `template` from `start` on; every occurrence copies its payer, amount, participants and details.
+    #[inline(always)]
+    pub fn new(template: Payment, frequency: Frequency, start: NaiveDate) -> Self {
+        Self {
+            template,
+            frequency,
+            start,
+            end: None,
+            participants: Vec::new(),
+        }
+    }
+
+    /// The last day the payment can happen on.
+    #[inline(always)]
+    pub const fn until(mut self, end: NaiveDate) -> Self {
+        self.end = Some(end);
+        self
+    }
+
+    /// From `date` on, the payment is for `to` instead, such as when a flatmate moves in or out.
+    #[inline(always)]
+    pub fn participants_from(mut self, date: NaiveDate, to: &[Person]) -> Self {
+        self.participants.retain(|(d, _)| *d != date);
+        self.participants.push((date, to.to_vec()));
+        self.participants.sort_by_key(|(d, _)| *d);
+        self
+    }
+
+    #[inline(always)]
+    pub const fn template(&self) -> &Payment {
+        &self.template
+    }
+
+    #[inline(always)]
+    pub const fn frequency(&self) -> Frequency {
+        self.frequency
+    }
+
+    /// Who the payment is for on `date`.
+    #[inline(always)]
+    pub fn participants_on(&self, date: NaiveDate) -> &[Person] {
+        self.participants
+            .iter()
+            .rev()
+            .find(|(from, _)| *from <= date)
+            .map_or(self.template.to(), |(_, to)| to.as_slice())
+    }
+
+    /// The days the payment happens on between `from` and `to`, both included.
+    pub fn occurrences(&self, from: NaiveDate, to: NaiveDate) -> Vec<NaiveDate> {
+        let last = self.end.map_or(to, |end| end.min(to));
+
+        (0..)
+            .map_while(|n: u32| match self.frequency {
+                Frequency::Weekly => self.start.checked_add_days(Days::new(7 * u64::from(n))),
+                Frequency::Monthly => self.start.checked_add_months(Months::new(n)),
+                Frequency::Yearly => self.start.checked_add_months(Months::new(12 * n)),
+            })
+            .take_while(|date| *date <= last)
+            .filter(|date| *date >= from)
+            .collect()
+    }
+
+    /// One payment per occurrence between `from` and `to`, both included, dated on the day it happens.
+    /// When the template has an id, each payment's id is the template's followed by `@` and the date.
+    pub fn expand(&self, from: NaiveDate, to: NaiveDate) -> Vec<Payment> {
+        self.occurrences(from, to)
+            .into_iter()
+            .map(|date| {
+                let mut payment = self
+                    .template
+                    .to_builder()
+                    .to(self.participants_on(date))
+                    .date(date);
+
+                if let Some(id) = self.template.id() {
+                    payment = payment.id(PaymentId::new(format!("{id}@{date}")));
+                }
+
+                payment.build()
+            })
+            .collect()
+    }
+}
+
+impl Payments {
+    /// Records every occurrence of `recurring` between `from` and `to`, both included.
+    #[inline(always)]
+    pub fn record_recurring(
+        &mut self,
+        recurring: &RecurringPayment,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) {
+        for payment in recurring.expand(from, to) {
+            self.record(payment);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Money;
+
+    #[test]
+    fn test_monthly_rent_with_a_new_flatmate() {
+        let (a, b, c) = (Person::new("A"), Person::new("B"), Person::new("C"));
+        let date = |m, d| NaiveDate::from_ymd_opt(2024, m, d).unwrap();
+
+        let rent = RecurringPayment::new(
+            Payment::builder()
+                .from(a.clone())
+                .amount(Money::new(90000))
+                .to(&[a.clone(), b.clone()])
+                .id(PaymentId::new("rent"))
+                .category("housing")
+                .build(),
+            Frequency::Monthly,
+            date(1, 31),
+        )
+        .participants_from(date(3, 15), &[a.clone(), b.clone(), c.clone()])
+        .until(date(5, 1));
+
+        assert_eq!(
+            vec![date(2, 29), date(3, 31), date(4, 30)],
+            rent.occurrences(date(2, 1), date(12, 31))
+        );
+
+        let mut payments = Payments::default();
+        payments.record_recurring(&rent, date(1, 1), date(12, 31));
+
+        assert_eq!(4, payments.raw().len());
+        assert_eq!(
+            Some(&PaymentId::new("rent@2024-03-31")),
+            payments.raw()[2].id()
+        );
+        assert_eq!(Some("housing"), payments.raw()[2].category());
+        assert_eq!(
+            vec![
+                (b.clone(), Money::new(-150000)),
+                (a.clone(), Money::new(210000)),
+                (c, Money::new(-60000))
+            ],
+            payments.balances()
+        );
+
+        let groceries = RecurringPayment::new(
+            Payment::new(b.clone(), Money::new(5000), std::slice::from_ref(&b)),
+            Frequency::Weekly,
+            date(1, 1),
+        );
+        assert_eq!(5, groceries.expand(date(1, 1), date(1, 31)).len());
+
+        // The end date is included when the payment happens on it, and nothing happens after it.
+        let weekly = groceries.clone().until(date(1, 15));
+        assert_eq!(
+            vec![date(1, 1), date(1, 8), date(1, 15)],
+            weekly.occurrences(date(1, 1), date(12, 31))
+        );
+        assert!(weekly.occurrences(date(1, 16), date(12, 31)).is_empty());
+        assert!(groceries
+            .clone()
+            .until(date(1, 1).pred_opt().unwrap())
+            .occurrences(date(1, 1), date(12, 31))
+            .is_empty());
+
+        // A yearly payment on February 29th falls on the 28th in other years, up to the end date.
+        let yearly = RecurringPayment::new(
+            Payment::new(a.clone(), Money::new(1000), std::slice::from_ref(&b)),
+            Frequency::Yearly,
+            date(2, 29),
+        )
+        .until(NaiveDate::from_ymd_opt(2026, 2, 28).unwrap());
+        assert_eq!(
+            vec![
+                date(2, 29),
+                NaiveDate::from_ymd_opt(2025, 2, 28).unwrap(),
+                NaiveDate::from_ymd_opt(2026, 2, 28).unwrap(),
+            ],
+            yearly.occurrences(date(1, 1), NaiveDate::from_ymd_opt(2030, 1, 1).unwrap())
+        );
+    }
+}
@@ -0,0 +1,374 @@
+use chrono::{Datelike, Days, NaiveDate};
+
+use crate::{Money, Payment, Payments, Person};
+
+/// How spending is bucketed over time in a [`Report`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    /// Weeks start on Monday.
+    Week,
+    #[default]
+    Month,
+}
+
+impl Period {
+    /// The first day of the period `date` falls in.
+    #[inline(always)]
+    pub fn start(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Day => date,
+            Self::Week => date - Days::new(u64::from(date.weekday().num_days_from_monday())),
+            Self::Month => date.with_day(1).unwrap_or(date),
+        }
+    }
+}
+
+/// What a single person spent and consumed, in a [`Report`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersonReport {
+    pub person: Person,
+    /// The total of the payments they made.
+    pub spent: Money,
+    /// The total of their parts of every payment they took part in.
+    pub consumed: Money,
+    /// Their part of everything that was consumed, between 0 and 1.
+    pub share: f64,
+}
+
+/// A summary of the spending recorded in some [`Payments`], built with [`Report::builder`].
+#[derive(Debug, Default, Clone)]
+pub struct Report {
+    /// The total of every payment.
+    pub total: Money,
+    /// Everyone involved, in the order they first appear.
+    pub people: Vec<PersonReport>,
+    /// Spending per category, largest first; `None` gathers the payments without one.
+    pub categories: Vec<(Option<String>, Money)>,
+    /// Spending per period, keyed by the first day of the period, in chronological order.
+    pub periods: Vec<(NaiveDate, Money)>,
+    /// The total of the payments without a date, which are left out of `periods`.
+    pub undated: Money,
+    /// The largest payments, largest first.
+    pub largest: Vec<Payment>,
+}
+
+impl Report {
+    #[inline(always)]
+    pub fn builder() -> ReportBuilder {
+        ReportBuilder::default()
+    }
+
+    /// Renders the report as Markdown tables.
+    pub fn to_markdown(&self) -> String {
+        self.render(&|title, headers, rows| {
+            let mut out = format!("## {title}\n\n| {} |\n", headers.join(" | "));
+            out.push_str(&format!("|{}\n", " --- |".repeat(headers.len())));
+
+            for row in rows {
+                let row = row.iter().map(|cell| cell.replace('|', "\\|"));
+                out.push_str(&format!("| {} |\n", row.collect::<Vec<_>>().join(" | ")));
+            }
+
+            out
+        })
+        .join("\n")
+    }
+
+    /// Renders the report as HTML tables.
+    pub fn to_html(&self) -> String {
+        self.render(&|title, headers, rows| {
+            let cells = |tag: &str, cells: &[String]| {
+                cells
+                    .iter()
+                    .map(|cell| format!("<{tag}>{}</{tag}>", escape(cell)))
+                    .collect::<String>()
+            };
+
+            let headers = headers.iter().map(|h| h.to_string()).collect::<Vec<_>>();
+            let mut out = format!(
+                "<h2>{}</h2>\n<table>\n<thead><tr>{}</tr></thead>\n<tbody>\n",
+                escape(title),
+                cells("th", &headers)
+            );
+
+            for row in rows {
+                out.push_str(&format!("<tr>{}</tr>\n", cells("td", row)));
+            }
+
+            out.push_str("</tbody>\n</table>\n");
+            out
+        })
+        .join("")
+    }
+
+    /// Calls `table` with the title, headers and rows of every section of the report.
+    fn render(&self, table: RenderTable) -> Vec<String> {
+        let people = self
+            .people
+            .iter()
+            .map(|p| {
+                vec![
+                    p.person.to_string(),
+                    p.spent.to_string(),
+                    p.consumed.to_string(),
+                    format!("{:.1}%", p.share * 100.0),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let categories = self
+            .categories
+            .iter()
+            .map(|(category, amount)| {
+                vec![
+                    category.clone().unwrap_or_else(|| "-".to_owned()),
+                    amount.to_string(),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        let mut periods = self
+            .periods
+            .iter()
+            .map(|(start, amount)| vec![start.to_string(), amount.to_string()])
+            .collect::<Vec<_>>();
+
+        if self.undated.raw() != 0 {
+            periods.push(vec!["-".to_owned(), self.undated.to_string()]);
+        }
+
+        let largest = self
+            .largest
+            .iter()
+            .map(|p| {
+                vec![
+                    p.from().to_string(),
+                    p.amount().to_string(),
+                    p.date().map(|d| d.to_string()).unwrap_or_default(),
+                    p.description().unwrap_or_default().to_owned(),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        vec![
+            table(
+                &format!("Spending ({})", self.total),
+                &["Person", "Spent", "Consumed", "Share"],
+                &people,
+            ),
+            table("By category", &["Category", "Amount"], &categories),
+            table("By period", &["From", "Amount"], &periods),
+            table(
+                "Largest expenses",
+                &["Paid by", "Amount", "Date", "Description"],
+                &largest,
+            ),
+        ]
+    }
+}
+
+/// Renders a table from its title, headers and rows.
+type RenderTable<'a> = &'a dyn Fn(&str, &[&str], &[Vec<String>]) -> String;
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[derive(Debug)]
+pub struct ReportBuilder {
+    period: Period,
+    largest: usize,
+}
+
+impl Default for ReportBuilder {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            period: Period::default(),
+            largest: 5,
+        }
+    }
+}
+
+impl ReportBuilder {
+    /// How spending is bucketed over time, by month by default.
+    #[inline(always)]
+    pub const fn period(mut self, period: Period) -> Self {
+        self.period = period;
+        self
+    }
+
+    /// How many of the largest payments to list, 5 by default.
+    #[inline(always)]
+    pub const fn largest(mut self, count: usize) -> Self {
+        self.largest = count;
+        self
+    }
+
+    pub fn build(self, payments: &Payments) -> Report {
+        let mut report = Report::default();
+
+        let add = |entries: &mut Vec<(Person, i32, i32)>, person: &Person| match entries
+            .iter()
+            .position(|(p, _, _)| p == person)
+        {
+            Some(index) => index,
+            None => {
+                entries.push((person.clone(), 0, 0));
+                entries.len() - 1
+            }
+        };
+
+        let mut people: Vec<(Person, i32, i32)> = Vec::new();
+        let mut categories: Vec<(Option<String>, i32)> = Vec::new();
+        let mut periods: Vec<(NaiveDate, i32)> = Vec::new();
+
+        for payment in payments.raw() {
+            let amount = payment.amount().raw();
+            report.total = Money::new(report.total.raw() + amount);
+
+            let payer = add(&mut people, payment.from());
+            people[payer].1 += amount;
+
+            for (participant, share) in payment.shares() {
+                let index = add(&mut people, &participant);
+                people[index].2 += share.raw();
+            }
+
+            let category = payment.category().map(str::to_owned);
+            match categories.iter_mut().find(|(c, _)| *c == category) {
+                Some((_, total)) => *total += amount,
+                None => categories.push((category, amount)),
+            }
+
+            match payment.date().map(|date| self.period.start(date)) {
+                Some(start) => match periods.iter_mut().find(|(s, _)| *s == start) {
+                    Some((_, total)) => *total += amount,
+                    None => periods.push((start, amount)),
+                },
+                None => report.undated = Money::new(report.undated.raw() + amount),
+            }
+        }
+
+        let consumed = people.iter().map(|(_, _, consumed)| consumed).sum::<i32>();
+
+        report.people = people
+            .into_iter()
+            .map(|(person, spent, own)| PersonReport {
+                person,
+                spent: Money::new(spent),
+                consumed: Money::new(own),
+                share: match consumed {
+                    0 => 0.0,
+                    _ => f64::from(own) / f64::from(consumed),
+                },
+            })
+            .collect();
+
+        categories.sort_by_key(|(_, total)| -total);
+        report.categories = categories
+            .into_iter()
+            .map(|(category, total)| (category, Money::new(total)))
+            .collect();
+
+        periods.sort_by_key(|(start, _)| *start);
+        report.periods = periods
+            .into_iter()
+            .map(|(start, total)| (start, Money::new(total)))
+            .collect();
+
+        let mut largest = payments.raw().clone();
+        largest.sort_by_key(|payment| -payment.amount().raw());
+        largest.truncate(self.largest);
+        report.largest = largest;
+
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trip_report() {
+        let (a, b, c) = (Person::new("A"), Person::new("B"), Person::new("C"));
+        let day = |d| NaiveDate::from_ymd_opt(2024, 5, d).unwrap();
+
+        let payments = Payments::new(&[
+            Payment::builder()
+                .from(a.clone())
+                .amount(Money::new(9000))
+                .to(&[a.clone(), b.clone(), c.clone()])
+                .date(day(6))
+                .category("lodging")
+                .description("Cabin <3 nights>")
+                .build(),
+            Payment::builder()
+                .from(b.clone())
+                .amount(Money::new(3000))
+                .to(&[a.clone(), b.clone()])
+                .date(day(8))
+                .category("food")
+                .build(),
+            Payment::builder()
+                .from(c.clone())
+                .amount(Money::new(1200))
+                .to(&[a.clone(), b.clone(), c.clone()])
+                .date(day(13))
+                .category("food")
+                .build(),
+            Payment::new(a.clone(), Money::new(600), std::slice::from_ref(&c)),
+        ]);
+
+        let report = Report::builder()
+            .period(Period::Week)
+            .largest(2)
+            .build(&payments);
+
+        assert_eq!(Money::new(13800), report.total);
+        assert_eq!(
+            vec![
+                (a.clone(), Money::new(9600), Money::new(5200)),
+                (b.clone(), Money::new(3000), Money::new(4900)),
+                (c.clone(), Money::new(1200), Money::new(3700)),
+            ],
+            report
+                .people
+                .iter()
+                .map(|p| (p.person.clone(), p.spent, p.consumed))
+                .collect::<Vec<_>>()
+        );
+        assert!((report.people.iter().map(|p| p.share).sum::<f64>() - 1.0).abs() < 1e-9);
+        assert_eq!(
+            vec![
+                (Some("lodging".to_owned()), Money::new(9000)),
+                (Some("food".to_owned()), Money::new(4200)),
+                (None, Money::new(600)),
+            ],
+            report.categories
+        );
+        assert_eq!(
+            vec![(day(6), Money::new(12000)), (day(13), Money::new(1200))],
+            report.periods
+        );
+        assert_eq!(Money::new(600), report.undated);
+        assert_eq!(
+            vec![9000, 3000],
+            report
+                .largest
+                .iter()
+                .map(|p| p.amount().raw())
+                .collect::<Vec<_>>()
+        );
+
+        assert!(report
+            .to_markdown()
+            .contains("| A | 96.00 | 52.00 | 37.7% |"));
+        assert!(report.to_html().contains("<td>Cabin &lt;3 nights&gt;</td>"));
+    }
+}
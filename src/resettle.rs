@@ -0,0 +1,153 @@
+use crate::{Constraints, Money, Obligation, Obligations, Payment, Payments, Person, SolverError};
+
+impl Obligations {
+    /// Updates this settlement for one more `payment`, changing as few of the existing transfers
+    /// as possible instead of solving everything again.
+    ///
+    /// Only what `payment` adds to everyone's balance is settled, between people who already pay
+    /// each other whenever possible, and then netted into the existing transfers.
+    ///
+    /// A payment made [`crate::PaymentBuilder::to_members`], or [`crate::PaymentBuilder::weighted`] without weights
+    /// of its own, can't be split without its group: it is a [`SolverError::NeedsGroup`], see [`crate::Group::resettle`].
+    #[inline(always)]
+    pub fn resettle(&self, payment: &Payment) -> Result<Obligations, SolverError> {
+        if payment.is_to_members() || (payment.is_weighted() && payment.weights().is_empty()) {
+            return Err(SolverError::NeedsGroup);
+        }
+
+        self.resettle_by(&Payments::new(std::slice::from_ref(payment)).balances())
+    }
+
+    /// Updates this settlement for what everyone's balance moved by.
+    pub(crate) fn resettle_by(
+        &self,
+        delta: &[(Person, Money)],
+    ) -> Result<Obligations, SolverError> {
+        let mut preferred = Constraints::builder();
+        for o in self.raw() {
+            preferred.prefer(o.from.clone(), o.to.clone());
+        }
+
+        let mut transfers = self.raw().clone();
+
+        for o in preferred.build().settle(delta)?.raw() {
+            let mut left = o.amount.raw();
+
+            if let Some(reverse) = transfers
+                .iter_mut()
+                .find(|t| t.from == o.to && t.to == o.from)
+            {
+                let netted = left.min(reverse.amount.raw());
+                reverse.amount = Money::new(reverse.amount.raw() - netted);
+                left -= netted;
+            }
+
+            if left == 0 {
+                continue;
+            }
+
+            match transfers
+                .iter_mut()
+                .find(|t| t.from == o.from && t.to == o.to)
+            {
+                Some(existing) => existing.amount = Money::new(existing.amount.raw() + left),
+                None => transfers.push(
+                    Obligation::builder()
+                        .from(o.from.clone())
+                        .to(o.to.clone())
+                        .amount(Money::new(left))
+                        .build(),
+                ),
+            }
+        }
+
+        transfers.retain(|t| t.amount.raw() > 0);
+
+        Ok(Obligations::new(&transfers))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use super::*;
+    use crate::{Group, ObligationBuilder};
+
+    #[test]
+    fn test_resettle_keeps_unrelated_transfers() {
+        let (a, b, c, d, e) = (
+            Person::new("A"),
+            Person::new("B"),
+            Person::new("C"),
+            Person::new("D"),
+            Person::new("E"),
+        );
+
+        let mut payments = Payments::builder()
+            .record(Payment::new(
+                a.clone(),
+                Money::new(4000),
+                &[a.clone(), b.clone()],
+            ))
+            .record(Payment::new(
+                c.clone(),
+                Money::new(6000),
+                &[c.clone(), d.clone(), e.clone()],
+            ))
+            .build();
+
+        let before = payments.who_pays_whom().unwrap();
+
+        // A coffee between B and A only touches the transfer between them.
+        let coffee = Payment::new(b.clone(), Money::new(500), &[a.clone(), b.clone()]);
+        let after = before.resettle(&coffee).unwrap();
+
+        let unchanged = |o: &&Obligation| o.from != b;
+        assert_eq!(
+            before.raw().iter().filter(unchanged).collect::<Vec<_>>(),
+            after.raw().iter().filter(unchanged).collect::<Vec<_>>()
+        );
+        assert!(after
+            .raw()
+            .contains(&ObligationBuilder::new(b.clone(), a.clone(), Money::new(1750)).build()));
+
+        payments.record(coffee);
+        let mut expected = payments.balances();
+        let mut actual = after.balances();
+        expected.retain(|(_, balance)| balance.raw() != 0);
+        expected.sort_by_key(|(p, _)| p.raw().clone());
+        actual.sort_by_key(|(p, _)| p.raw().clone());
+        assert_eq!(expected, actual);
+
+        // In a group, the payment is split by the weights first.
+        let mut group = Group::new(&[a.clone(), b.clone()]);
+        group.set_weight(a.clone(), NonZeroU32::new(3).unwrap());
+        group.set_weight(b.clone(), NonZeroU32::MIN);
+        group.record(Payment::new(
+            a.clone(),
+            Money::new(4000),
+            &[a.clone(), b.clone()],
+        ));
+
+        let before = group.who_pays_whom().unwrap();
+        let rent = Payment::builder()
+            .from(b.clone())
+            .amount(Money::new(8000))
+            .to(&[a.clone(), b.clone()])
+            .weighted()
+            .build();
+        assert!(matches!(
+            before.resettle(&rent),
+            Err(SolverError::NeedsGroup)
+        ));
+        let after = group.resettle(&before, &rent).unwrap();
+
+        group.record(rent);
+        assert_eq!(group.who_pays_whom().unwrap(), after);
+        assert_eq!(
+            vec![ObligationBuilder::new(a, b, Money::new(4000)).build()],
+            *after.raw()
+        );
+    }
+}
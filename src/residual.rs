@@ -0,0 +1,117 @@
+use crate::{Money, Obligation, Obligations, Person};
+
+/// The obligations dropped by [`crate::Solver::with_threshold`] for being too small to bother with.
+/// They are not forgiven for good: feeding them to the next settlement with
+/// [`crate::Solver::with_residuals`] carries them over, so amounts still reconcile.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Residuals(Vec<Obligation>);
+
+impl Residuals {
+    #[inline(always)]
+    pub fn new(obligations: &[Obligation]) -> Self {
+        Self(obligations.to_vec())
+    }
+
+    #[inline(always)]
+    pub const fn raw(&self) -> &Vec<Obligation> {
+        &self.0
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// What everyone is still owed (positive) or still owes (negative) after the settlement.
+    #[inline(always)]
+    pub fn balances(&self) -> Vec<(Person, Money)> {
+        Obligations::new(&self.0).balances()
+    }
+}
+
+/// Splits `obligations` into the ones worth settling and the ones below `threshold`.
+pub(crate) fn apply_threshold(
+    obligations: &Obligations,
+    threshold: Money,
+) -> (Obligations, Residuals) {
+    let (kept, dropped): (Vec<_>, Vec<_>) = obligations
+        .raw()
+        .iter()
+        .cloned()
+        .partition(|o| o.amount.raw() >= threshold.raw());
+
+    (Obligations::new(&kept), Residuals(dropped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ObligationBuilder, Payment, Payments};
+
+    #[test]
+    fn test_threshold_carries_residuals_over() {
+        let (a, b, c) = (Person::new("A"), Person::new("B"), Person::new("C"));
+
+        let payments = Payments::builder()
+            .record(Payment::new(
+                a.clone(),
+                Money::new(3000),
+                &[a.clone(), b.clone()],
+            ))
+            .record(Payment::new(
+                a.clone(),
+                Money::new(14),
+                &[a.clone(), c.clone()],
+            ))
+            .build();
+
+        // A zero threshold drops nothing, however small.
+        let (obligations, residuals) = payments
+            .solver()
+            .with_threshold(Money::new(0))
+            .solve_with_residuals()
+            .unwrap();
+        assert_eq!(payments.who_pays_whom().unwrap(), obligations);
+        assert_eq!(2, obligations.len());
+        assert!(residuals.is_empty());
+
+        let (obligations, residuals) = payments
+            .solver()
+            .with_threshold(Money::new(100))
+            .solve_with_residuals()
+            .unwrap();
+
+        assert_eq!(
+            vec![ObligationBuilder::new(b.clone(), a.clone(), Money::new(1500)).build()],
+            *obligations.raw()
+        );
+        assert_eq!(
+            vec![(c.clone(), Money::new(-7)), (a.clone(), Money::new(7))],
+            residuals.balances()
+        );
+
+        // Next period C spends a bit more, and the 7 cents are no longer negligible.
+        let next = Payments::builder()
+            .record(Payment::new(
+                a.clone(),
+                Money::new(200),
+                &[a.clone(), c.clone()],
+            ))
+            .build();
+
+        let (obligations, residuals) = next
+            .solver()
+            .with_threshold(Money::new(100))
+            .with_residuals(&residuals)
+            .solve_with_residuals()
+            .unwrap();
+
+        assert_eq!(
+            vec![ObligationBuilder::new(c, a, Money::new(107)).build()],
+            *obligations.raw()
+        );
+        assert!(residuals.is_empty());
+    }
+}
@@ -1,8 +1,20 @@
-use petgraph::Graph;
+use petgraph::{
+    graph::{EdgeIndex, NodeIndex},
+    Graph,
+};
 use std::convert::From;
 use thiserror::Error;
 
-use crate::{obligation::Obligations, Money, Obligation, Person};
+use crate::{
+    constraints::Constraints,
+    limits::TransferLimits,
+    obligation::Obligations,
+    provenance::{combine, scale, Provenance, Sources},
+    residual::{apply_threshold, Residuals},
+    strategy::{settle_through_hub, Strategy},
+    trace::{SolverEvent, SolverPass, SolverTrace},
+    Money, Obligation, ObligationBuilder, Person,
+};
 
 #[derive(Error, Debug)]
 pub enum SolverError {
@@ -14,9 +26,27 @@ pub enum SolverError {
 
     #[error("could not find weight for edge index {0}")]
     NoWeightForEdge(usize),
+
+    #[error(
+        "could not settle the debt of {0} to {1} without a forbidden transfer or a shared payment method"
+    )]
+    UnsatisfiableConstraints(Person, Person),
+
+    #[error("the payment is split among a group's members or by its weights, resettle it through the group")]
+    NeedsGroup,
 }
 
-pub struct Solver(pub(crate) petgraph::Graph<String, i32>);
+pub struct Solver {
+    pub(crate) graph: petgraph::Graph<String, i32>,
+    trace: Option<Vec<SolverEvent>>,
+    constraints: Constraints,
+    limits: TransferLimits,
+    strategy: Strategy,
+    threshold: Option<Money>,
+    residuals: Residuals,
+    provenance: Provenance,
+    with_sources: bool,
+}
 
 impl Solver {
     #[inline(always)]
@@ -24,42 +54,219 @@ impl Solver {
         Self::from(o)
     }
 
+    /// Reroutes the transfers `constraints` forbid and settles preferred pairs first, see [`Constraints`].
+    #[inline(always)]
+    pub fn with_constraints(mut self, constraints: Constraints) -> Self {
+        self.constraints = constraints;
+        self
+    }
+
+    /// Caps single transfers and tags them with a payment method, see [`TransferLimits`].
+    /// People without a payment method in common never pay each other directly.
+    #[inline(always)]
+    pub fn with_limits(mut self, limits: TransferLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Shapes the settlement, see [`Strategy`].
+    /// With [`Strategy::Hub`], constraints are only checked, since every transfer has to go through the treasurer.
+    #[inline(always)]
+    pub fn with_strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Drops obligations below `threshold` from the settlement, keeping them as [`Residuals`].
+    #[inline(always)]
+    pub fn with_threshold(mut self, threshold: Money) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+
+    /// Carries over the residuals left by a previous settlement, on top of what is owed now.
+    #[inline(always)]
+    pub fn with_residuals(mut self, residuals: &Residuals) -> Self {
+        for obligation in residuals.raw() {
+            self.add_obligation(obligation);
+        }
+        self
+    }
+
     #[inline(always)]
     pub fn solve(&mut self) -> Result<Obligations, SolverError> {
+        let mut people = self
+            .graph
+            .node_weights()
+            .cloned()
+            .map(Person::new)
+            .collect::<Vec<_>>();
+        people.sort();
+        people.dedup();
+
+        let mut obligations = match self.strategy.clone() {
+            Strategy::MinimizeTransfers => {
+                // Preferred pairs settle between themselves first, and the passes settle the rest.
+                let preferred = self
+                    .constraints
+                    .settle_preferred(&self.format_out()?.balances());
+                for o in &preferred {
+                    self.add_obligation(
+                        &ObligationBuilder::new(o.to.clone(), o.from.clone(), o.amount).build(),
+                    );
+                }
+
+                self.minimize()?.merge(&preferred)
+            }
+            Strategy::Hub(treasurer) => {
+                self.record(SolverEvent::Pass(SolverPass::SettleThroughHub));
+                settle_through_hub(&treasurer, &self.format_out()?.balances())
+            }
+        };
+
+        let constraints = match self.limits.is_empty() {
+            true => self.constraints.clone(),
+            false => self.limits.constrain(&self.constraints, &people),
+        };
+
+        if let Strategy::Hub(_) = self.strategy {
+            let forbidden = obligations
+                .raw()
+                .iter()
+                .find(|o| !constraints.allows(&o.from, &o.to));
+
+            if let Some(o) = forbidden {
+                return Err(SolverError::UnsatisfiableConstraints(
+                    o.from.clone(),
+                    o.to.clone(),
+                ));
+            }
+        }
+
+        if !constraints.is_empty() && self.strategy == Strategy::MinimizeTransfers {
+            self.record(SolverEvent::Pass(SolverPass::ApplyConstraints));
+            obligations = constraints.reroute(&obligations, &people)?;
+        }
+
+        if let Some(threshold) = self.threshold {
+            self.record(SolverEvent::Pass(SolverPass::ApplyThreshold));
+            (obligations, self.residuals) = apply_threshold(&obligations, threshold);
+        }
+
+        if !self.limits.is_empty() {
+            self.record(SolverEvent::Pass(SolverPass::ApplyLimits));
+            obligations = self.limits.apply(&obligations);
+        }
+
+        obligations.sort();
+
+        Ok(obligations)
+    }
+
+    #[inline(always)]
+    fn minimize(&mut self) -> Result<Obligations, SolverError> {
+        self.record(SolverEvent::Pass(SolverPass::RemoveDoublyConnectedEdges));
         self.pass_remove_doubly_connected_edges();
+        self.record(SolverEvent::Pass(SolverPass::SimplifyDoubleTarget));
         self.pass_simplify_double_target();
+        self.record(SolverEvent::Pass(SolverPass::RemoveSameWeightTarget));
         self.pass_remove_same_weight_target();
+        // Chaining debts in the third pass can leave two people paying each other, net them again.
+        self.record(SolverEvent::Pass(SolverPass::RemoveDoublyConnectedEdges));
+        self.pass_remove_doubly_connected_edges();
+        self.record(SolverEvent::Pass(SolverPass::RemoveZeroEdges));
         self.pass_remove_zero_edges();
         self.format_out()
     }
 
+    /// Same as [`Solver::solve`], but also returns the obligations dropped by [`Solver::with_threshold`].
+    #[inline(always)]
+    pub fn solve_with_residuals(&mut self) -> Result<(Obligations, Residuals), SolverError> {
+        let obligations = self.solve()?;
+
+        Ok((obligations, std::mem::take(&mut self.residuals)))
+    }
+
+    /// Same as [`Solver::solve`], but every obligation lists the payments behind it in `sources`,
+    /// followed through the four passes. Amounts are signed and add up to the obligation's amount:
+    /// a payment made by the debtor shows up as negative, since it reduced what they owe.
+    ///
+    /// Transfers rerouted by constraints, split by limits or going through [`Strategy::Hub`] have no sources.
+    #[inline(always)]
+    pub fn solve_with_provenance(&mut self) -> Result<Obligations, SolverError> {
+        self.with_sources = true;
+
+        let obligations = self.solve();
+        self.with_sources = false;
+
+        obligations
+    }
+
+    /// Same as [`Solver::solve`], but also returns every step the passes took to get there.
+    #[inline(always)]
+    pub fn solve_traced(&mut self) -> Result<(Obligations, SolverTrace), SolverError> {
+        self.trace = Some(Vec::new());
+
+        let obligations = self.solve();
+        let events = self.trace.take().unwrap_or_default();
+
+        Ok((obligations?, SolverTrace::new(&events)))
+    }
+
+    #[inline(always)]
+    fn record(&mut self, event: SolverEvent) {
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(event);
+        }
+    }
+
+    #[inline(always)]
+    fn person(&self, node: NodeIndex) -> Person {
+        Person::new(self.graph[node].clone())
+    }
+
     /// First Pass
     /// Reduce doubly connected edges to a single edge connection.
     /// The resulting direction is dictated by subtracting the edges' weights.
     /// In case the result is zero, then both edges are removed.
     #[inline(always)]
     fn pass_remove_doubly_connected_edges(&mut self) {
-        for edge in self.0.edge_indices() {
+        for edge in self.graph.edge_indices() {
             // Waiting for https://github.com/rust-lang/rust/issues/53667 to be stabilized...
-            if let Some((e1_source, e1_target)) = self.0.edge_endpoints(edge) {
-                if let Some(e2) = self.0.find_edge(e1_target, e1_source) {
-                    if let Some(e1) = self.0.find_edge(e1_source, e1_target) {
+            if let Some((e1_source, e1_target)) = self.graph.edge_endpoints(edge) {
+                if let Some(e2) = self.graph.find_edge(e1_target, e1_source) {
+                    if let Some(e1) = self.graph.find_edge(e1_source, e1_target) {
                         if let (Some(w1), Some(w2)) =
-                            (self.0.edge_weight(e1), self.0.edge_weight(e2))
+                            (self.graph.edge_weight(e1), self.graph.edge_weight(e2))
                         {
-                            match (w1, w2) {
+                            let (w1, w2) = (*w1, *w2);
+                            let (p1, p2) = (self.provenance.get(e1), self.provenance.get(e2));
+
+                            let (from, to, amount, cancelled) = match (w1, w2) {
                                 _ if w1 > w2 => {
-                                    self.0.update_edge(e1_source, e1_target, w1 - w2);
-                                    self.0.remove_edge(e2);
+                                    self.update_edge(e1_source, e1_target, w1 - w2, negate(&p2));
+                                    self.remove_edge(e2);
+                                    (e1_source, e1_target, w1 - w2, w2)
                                 }
                                 _ if w1 < w2 => {
-                                    self.0.update_edge(e1_target, e1_source, w2 - w1);
-                                    self.0.remove_edge(e1);
+                                    self.update_edge(e1_target, e1_source, w2 - w1, negate(&p1));
+                                    self.remove_edge(e1);
+                                    (e1_target, e1_source, w2 - w1, w1)
                                 }
                                 _ => {
-                                    self.0.remove_edge(e1);
-                                    self.0.remove_edge(e2);
+                                    self.remove_edge(e1);
+                                    self.remove_edge(e2);
+                                    (e1_source, e1_target, 0, w1)
                                 }
+                            };
+
+                            if cancelled != 0 {
+                                self.record(SolverEvent::Merged {
+                                    from: self.person(from),
+                                    to: self.person(to),
+                                    amount: Money::new(amount),
+                                    cancelled: Money::new(cancelled),
+                                });
                             }
                         }
                     }
@@ -92,24 +299,24 @@ impl Solver {
 
     #[inline(always)]
     fn simplify_double_target(&mut self) {
-        for edge in self.0.edge_indices() {
+        for edge in self.graph.edge_indices() {
             // This should always return something? :thinking:
             let (source, target) = self
-                .0
+                .graph
                 .edge_endpoints(edge)
                 .expect("endpoints from edge are none");
 
-            for node in self.0.node_indices() {
+            for node in self.graph.node_indices() {
                 if node == source || node == target {
                     continue;
                 }
 
-                if let Some(source_node_edge) = self.0.find_edge(source, node) {
-                    if let Some(target_node_edge) = self.0.find_edge(target, node) {
+                if let Some(source_node_edge) = self.graph.find_edge(source, node) {
+                    if let Some(target_node_edge) = self.graph.find_edge(target, node) {
                         let (edge_weight, source_node_weight, target_node_weight) = match (
-                            self.0.edge_weight(edge),
-                            self.0.edge_weight(source_node_edge),
-                            self.0.edge_weight(target_node_edge),
+                            self.graph.edge_weight(edge),
+                            self.graph.edge_weight(source_node_edge),
+                            self.graph.edge_weight(target_node_edge),
                         ) {
                             (
                                 Some(edge_weight),
@@ -126,19 +333,58 @@ impl Solver {
                             }
                         };
 
-                        self.0
-                            .update_edge(source, node, source_node_weight + edge_weight);
+                        self.record(SolverEvent::Rerouted {
+                            from: self.person(source),
+                            via: self.person(target),
+                            to: self.person(node),
+                            amount: Money::new(edge_weight),
+                        });
+
+                        let moved = self.provenance.get(edge);
+                        let target_sources = self.provenance.get(target_node_edge);
+
+                        self.update_edge(
+                            source,
+                            node,
+                            source_node_weight + edge_weight,
+                            moved.clone(),
+                        );
 
                         if target_node_weight - edge_weight > 0 {
-                            self.0
-                                .update_edge(target, node, target_node_weight - edge_weight);
+                            self.update_edge(
+                                target,
+                                node,
+                                target_node_weight - edge_weight,
+                                negate(&moved),
+                            );
                         } else {
-                            self.0.update_edge(target, node, 0);
-                            self.0
-                                .add_edge(node, target, edge_weight - target_node_weight);
+                            self.update_edge(target, node, 0, Sources::new());
+                            self.add_edge(
+                                node,
+                                target,
+                                edge_weight - target_node_weight,
+                                combine(&moved, &target_sources, -1),
+                            );
+
+                            self.record(match edge_weight - target_node_weight {
+                                0 => SolverEvent::Zeroed {
+                                    from: self.person(target),
+                                    to: self.person(node),
+                                },
+                                amount => SolverEvent::Reversed {
+                                    from: self.person(target),
+                                    to: self.person(node),
+                                    amount: Money::new(amount),
+                                },
+                            });
                         }
 
-                        self.0.update_edge(source, target, 0);
+                        self.update_edge(source, target, 0, Sources::new());
+
+                        self.record(SolverEvent::Zeroed {
+                            from: self.person(source),
+                            to: self.person(target),
+                        });
 
                         break;
                     }
@@ -150,23 +396,39 @@ impl Solver {
     /// Third Pass
     // If there's an edge A --[X]--> B and another B --[X]--> C, it can be reduced to A --[X]--> C
     fn pass_remove_same_weight_target(&mut self) {
-        for edge in self.0.edge_indices() {
-            if let Some((source, target)) = self.0.edge_endpoints(edge) {
-                let weight = self.0[edge];
+        for edge in self.graph.edge_indices() {
+            if let Some((source, target)) = self.graph.edge_endpoints(edge) {
+                let weight = self.graph[edge];
 
                 if weight == 0 {
                     continue;
                 }
 
-                for node in self.0.node_indices() {
-                    if let Some(found) = self.0.find_edge(target, node) {
-                        let next_weight = self.0[found];
+                let moved = self.provenance.get(edge);
+
+                for node in self.graph.node_indices() {
+                    if let Some(found) = self.graph.find_edge(target, node) {
+                        let next_weight = self.graph[found];
 
                         if next_weight == weight {
-                            self.0.update_edge(source, target, 0);
-                            self.0.update_edge(target, node, 0);
+                            // Both debts are settled by the new transfer, so it comes from both in equal parts.
+                            let sources =
+                                scale(&combine(&moved, &self.provenance.get(found), 1), weight);
 
-                            self.0.add_edge(source, node, weight);
+                            self.update_edge(source, target, 0, Sources::new());
+                            self.update_edge(target, node, 0, Sources::new());
+
+                            self.add_edge(source, node, weight, sources);
+
+                            self.record(SolverEvent::Rerouted {
+                                from: self.person(source),
+                                via: self.person(target),
+                                to: self.person(node),
+                                amount: Money::new(weight),
+                            });
+
+                            // The edge is settled now: chaining it again would create money.
+                            break;
                         }
                     }
                 }
@@ -176,7 +438,7 @@ impl Solver {
 
     #[inline(always)]
     fn non_zero_edges_count(&mut self) -> i32 {
-        self.0.edge_weights().fold(0, |acc, weight| {
+        self.graph.edge_weights().fold(0, |acc, weight| {
             if *weight != 0 {
                 return acc + 1;
             }
@@ -188,116 +450,214 @@ impl Solver {
     // https://github.com/petgraph/petgraph/issues/299
     fn pass_remove_zero_edges(&mut self) {
         let mut g = Graph::<String, i32>::new();
+        let mut provenance = Provenance::default();
 
-        for edge in self.0.edge_indices() {
-            if let Some((source, target)) = self.0.edge_endpoints(edge) {
-                if let Some(edge_weight) = self.0.edge_weight(edge) {
+        for edge in self.graph.edge_indices() {
+            if let Some((source, target)) = self.graph.edge_endpoints(edge) {
+                if let Some(edge_weight) = self.graph.edge_weight(edge) {
                     if *edge_weight == 0 {
+                        self.record(SolverEvent::Removed {
+                            from: self.person(source),
+                            to: self.person(target),
+                        });
                         continue;
                     }
 
-                    if let (Some(source_weight), Some(target_weight)) =
-                        (self.0.node_weight(source), self.0.node_weight(target))
-                    {
+                    if let (Some(source_weight), Some(target_weight)) = (
+                        self.graph.node_weight(source),
+                        self.graph.node_weight(target),
+                    ) {
                         let source = g.add_node(source_weight.clone());
                         let target = g.add_node(target_weight.clone());
                         g.add_edge(source, target, *edge_weight);
+                        provenance.push(self.provenance.get(edge));
                     }
                 }
             }
         }
 
-        self.0 = g;
+        self.graph = g;
+        self.provenance = provenance;
+    }
+
+    /// Adds `obligation` to the graph, on top of any existing edge between the same people.
+    #[inline(always)]
+    fn add_obligation(&mut self, obligation: &Obligation) {
+        let g = &mut self.graph;
+
+        let from = obligation.from.raw().clone();
+        let to = obligation.to.raw().clone();
+        let amount = obligation.amount.raw();
+
+        let from_exists = g
+            .node_indices()
+            .filter(|node| g[*node] == from)
+            .collect::<Vec<_>>();
+
+        let to_exists = g
+            .node_indices()
+            .filter(|node| g[*node] == to)
+            .collect::<Vec<_>>();
+
+        let existing = match (from_exists.first(), to_exists.first()) {
+            (Some(from), None) => {
+                let to = g.add_node(to);
+
+                g.add_edge(*from, to, amount);
+                None
+            }
+            (None, Some(to)) => {
+                let from = g.add_node(from);
+
+                g.add_edge(from, *to, amount);
+                None
+            }
+            (Some(from), Some(to)) => match g.find_edge(*from, *to) {
+                Some(existing_edge) => {
+                    let existing_weight = g.edge_weight(existing_edge).unwrap_or(&0);
+
+                    g.update_edge(*from, *to, existing_weight + amount);
+                    Some(existing_edge)
+                }
+                None => {
+                    g.add_edge(*from, *to, obligation.amount.raw());
+                    None
+                }
+            },
+            (None, None) => {
+                let from = g.add_node(from);
+                let to = g.add_node(to);
+
+                g.add_edge(from, to, obligation.amount.raw());
+                None
+            }
+        };
+
+        let sources = obligation
+            .sources
+            .iter()
+            .map(|(id, amount)| (id.clone(), amount.raw()))
+            .collect::<Sources>();
+
+        match existing {
+            Some(edge) => self.provenance.add(edge, &sources),
+            None => self.provenance.push(sources),
+        }
+    }
+
+    /// Same as [`Graph::update_edge`], adding `sources` to the ones behind the edge,
+    /// or dropping them all when its weight becomes zero.
+    #[inline(always)]
+    fn update_edge(&mut self, a: NodeIndex, b: NodeIndex, weight: i32, sources: Sources) {
+        let existing = self.graph.find_edge(a, b);
+        let edge = self.graph.update_edge(a, b, weight);
+
+        match existing {
+            None if weight == 0 => self.provenance.push(Sources::new()),
+            None => self.provenance.push(sources),
+            Some(_) if weight == 0 => self.provenance.clear(edge),
+            Some(_) => self.provenance.add(edge, &sources),
+        }
+    }
+
+    #[inline(always)]
+    fn add_edge(&mut self, a: NodeIndex, b: NodeIndex, weight: i32, sources: Sources) {
+        self.graph.add_edge(a, b, weight);
+        self.provenance.push(sources);
+    }
+
+    #[inline(always)]
+    fn remove_edge(&mut self, edge: EdgeIndex) {
+        if self.graph.remove_edge(edge).is_some() {
+            self.provenance.remove(edge);
+        }
     }
 
     #[inline(always)]
     fn format_out(&self) -> Result<Obligations, SolverError> {
         let mut obligations = Obligations::builder();
 
-        for edge in self.0.edge_indices() {
+        for edge in self.graph.edge_indices() {
             let endpoint = self
-                .0
+                .graph
                 .edge_endpoints(edge)
                 .ok_or_else(|| SolverError::NoEndpointForEdge(edge.index()))?;
 
             let from = self
-                .0
+                .graph
                 .node_weight(endpoint.0)
                 .ok_or_else(|| SolverError::NoWeightForNode(endpoint.0.index()))?;
 
             let to = self
-                .0
+                .graph
                 .node_weight(endpoint.1)
                 .ok_or_else(|| SolverError::NoWeightForNode(endpoint.1.index()))?;
 
             let weight = self
-                .0
+                .graph
                 .edge_weight(edge)
                 .ok_or_else(|| SolverError::NoWeightForEdge(edge.index()))?;
 
-            obligations.record(
-                Obligation::builder()
-                    .from(Person::new(from))
-                    .to(Person::new(to))
-                    .amount(Money::new(*weight))
-                    .build(),
-            );
+            let mut obligation = Obligation::builder()
+                .from(Person::new(from))
+                .to(Person::new(to))
+                .amount(Money::new(*weight))
+                .build();
+
+            if self.with_sources {
+                obligation.sources = self.provenance.sources(edge);
+            }
+
+            obligations.record(obligation);
         }
 
-        Ok(obligations.build())
+        let mut obligations = obligations.build();
+        obligations.sort();
+
+        Ok(obligations)
     }
 }
 
+/// `sources` with every amount negated.
+#[inline(always)]
+fn negate(sources: &Sources) -> Sources {
+    combine(&Sources::new(), sources, -1)
+}
+
 impl From<Obligations> for Solver {
     #[inline(always)]
     fn from(item: Obligations) -> Self {
-        let mut g = Graph::<String, i32>::new();
-
-        for obligation in item.raw() {
-            let from = obligation.from.raw().clone();
-            let to = obligation.to.raw().clone();
-            let amount = obligation.amount.raw();
-
-            let from_exists = g
-                .node_indices()
-                .filter(|node| g[*node] == from)
-                .collect::<Vec<_>>();
-
-            let to_exists = g
-                .node_indices()
-                .filter(|node| g[*node] == to)
-                .collect::<Vec<_>>();
-
-            match (from_exists.first(), to_exists.first()) {
-                (Some(from), None) => {
-                    let to = g.add_node(to);
-
-                    g.add_edge(*from, to, amount);
-                }
-                (None, Some(to)) => {
-                    let from = g.add_node(from);
-
-                    g.add_edge(from, *to, amount);
-                }
-                (Some(from), Some(to)) => match g.find_edge(*from, *to) {
-                    Some(existing_edge) => {
-                        let existing_weight = g.edge_weight(existing_edge).unwrap_or(&0);
-
-                        g.update_edge(*from, *to, existing_weight + amount);
-                    }
-                    None => {
-                        g.add_edge(*from, *to, obligation.amount.raw());
-                    }
-                },
-                (None, None) => {
-                    let from = g.add_node(from);
-                    let to = g.add_node(to);
+        let mut solver = Self {
+            graph: Graph::<String, i32>::new(),
+            trace: None,
+            constraints: Constraints::default(),
+            limits: TransferLimits::default(),
+            strategy: Strategy::default(),
+            threshold: None,
+            residuals: Residuals::default(),
+            provenance: Provenance::default(),
+            with_sources: false,
+        };
+
+        // Build the graph in a canonical order, so the result doesn't depend on the order of the payments.
+        let mut obligations = item.raw().clone();
+        obligations.sort_by(|a, b| (&a.to, &a.from, a.amount).cmp(&(&b.to, &b.from, b.amount)));
+
+        let mut people = obligations
+            .iter()
+            .flat_map(|o| [o.from.raw(), o.to.raw()])
+            .collect::<Vec<_>>();
+        people.sort();
+        people.dedup();
+
+        for person in people {
+            solver.graph.add_node(person.clone());
+        }
 
-                    g.add_edge(from, to, obligation.amount.raw());
-                }
-            }
+        for obligation in &obligations {
+            solver.add_obligation(obligation);
         }
 
-        Self(g)
+        solver
     }
 }
@@ -0,0 +1,88 @@
+use crate::{Money, Obligation, Obligations, Person};
+
+/// How the solver shapes the settlement, see [`crate::Solver::with_strategy`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub enum Strategy {
+    /// Run the solver passes, looking for as few transfers as possible between anyone.
+    #[default]
+    MinimizeTransfers,
+
+    /// Route every transfer through a treasurer: debtors pay the treasurer, and the treasurer pays creditors.
+    /// This takes at most N-1 transfers, with one point of contact.
+    Hub(Person),
+}
+
+/// Settles the balances through `treasurer`, debtors first, in the order they appear.
+pub(crate) fn settle_through_hub(treasurer: &Person, balances: &[(Person, Money)]) -> Obligations {
+    let mut obligations = Obligations::builder();
+
+    let debtors = balances.iter().filter(|(_, balance)| balance.raw() < 0);
+    let creditors = balances.iter().filter(|(_, balance)| balance.raw() > 0);
+
+    for (debtor, balance) in debtors.filter(|(p, _)| p != treasurer) {
+        obligations.record(
+            Obligation::builder()
+                .from(debtor.clone())
+                .to(treasurer.clone())
+                .amount(Money::new(-balance.raw()))
+                .build(),
+        );
+    }
+
+    for (creditor, balance) in creditors.filter(|(p, _)| p != treasurer) {
+        obligations.record(
+            Obligation::builder()
+                .from(treasurer.clone())
+                .to(creditor.clone())
+                .amount(*balance)
+                .build(),
+        );
+    }
+
+    obligations.build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ObligationBuilder, Payment, Payments};
+
+    #[test]
+    fn test_hub() {
+        let (a, b, c, t) = (
+            Person::new("A"),
+            Person::new("B"),
+            Person::new("C"),
+            Person::new("T"),
+        );
+
+        let payments = Payments::builder()
+            .record(Payment::new(
+                a.clone(),
+                Money::new(3000),
+                &[a.clone(), b.clone(), c.clone()],
+            ))
+            .record(Payment::new(
+                t.clone(),
+                Money::new(1000),
+                std::slice::from_ref(&c),
+            ))
+            .build();
+
+        let obligations = payments
+            .solver()
+            .with_strategy(Strategy::Hub(t.clone()))
+            .solve()
+            .unwrap();
+
+        // B owes A 10, C owes A 10 and T 5: everything goes through T.
+        let expected = Obligations::builder()
+            .record(ObligationBuilder::new(b, t.clone(), Money::new(1000)).build())
+            .record(ObligationBuilder::new(c, t.clone(), Money::new(1500)).build())
+            .record(ObligationBuilder::new(t.clone(), a, Money::new(2000)).build())
+            .build();
+
+        assert_eq!(expected, obligations);
+        assert!(obligations.raw().iter().all(|o| o.from == t || o.to == t));
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{Money, Payment, Payments, Person};
+
+impl Payments {
+    /// Who among `participants` should pay the next expense they share, so that balances drift
+    /// towards zero and fewer transfers are needed later, see [`who_pays_next`].
+    #[inline(always)]
+    pub fn who_pays_next(&self, participants: &[Person], amount: Option<Money>) -> Option<Person> {
+        who_pays_next(&self.balances(), participants, amount, |payment| {
+            Payments::new(std::slice::from_ref(payment)).balances()
+        })
+    }
+}
+
+/// Whoever owes the most among `participants` pays next. With the expected `amount`, it is whoever
+/// paying it for everyone leaves the least owed in total, then the smallest largest debt or credit.
+/// Ties go to the first of `participants`, and `delta` tells how a payment moves everyone's balance.
+pub(crate) fn who_pays_next(
+    balances: &[(Person, Money)],
+    participants: &[Person],
+    amount: Option<Money>,
+    delta: impl Fn(&Payment) -> Vec<(Person, Money)>,
+) -> Option<Person> {
+    let balance = |person: &Person| {
+        balances
+            .iter()
+            .find(|(p, _)| p == person)
+            .map_or(0, |(_, balance)| balance.raw())
+    };
+
+    let Some(amount) = amount else {
+        return participants.iter().min_by_key(|p| balance(p)).cloned();
+    };
+
+    participants
+        .iter()
+        .min_by_key(|payer| {
+            let payment = Payment::new((*payer).clone(), amount, participants);
+            let delta = delta(&payment);
+
+            let after = participants
+                .iter()
+                .map(|person| {
+                    let change = delta
+                        .iter()
+                        .find(|(p, _)| p == person)
+                        .map_or(0, |(_, change)| change.raw());
+
+                    (balance(person) + change).abs()
+                })
+                .collect::<Vec<_>>();
+
+            (after.iter().sum::<i32>(), after.iter().max().copied())
+        })
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Budget, Group, NaiveDate};
+
+    #[test]
+    fn test_whoever_owes_most_pays_next() {
+        let (a, b, c) = (Person::new("A"), Person::new("B"), Person::new("C"));
+
+        let mut group = Group::new(&[a.clone(), b.clone(), c.clone()]);
+        group.record(Payment::new(
+            a.clone(),
+            Money::new(9000),
+            &[a.clone(), b.clone(), c.clone()],
+        ));
+        group.record(Payment::new(
+            c.clone(),
+            Money::new(1500),
+            &[a.clone(), c.clone()],
+        ));
+
+        // A is owed 52.50, B owes 30.00 and C 22.50.
+        assert_eq!(Some(b.clone()), group.who_pays_next(None));
+        assert_eq!(Some(b.clone()), group.who_pays_next(Some(Money::new(9000))));
+
+        // Between A and C only, C owes the most.
+        assert_eq!(
+            Some(c.clone()),
+            group
+                .payments()
+                .who_pays_next(&[a.clone(), c.clone()], Some(Money::new(3000)))
+        );
+        assert_eq!(None, group.payments().who_pays_next(&[], None));
+
+        // C's budget is used up, so A and B would split the next expense: B paying it leaves less owed than C.
+        let mut group = Group::new(&[a.clone(), b.clone(), c.clone()]);
+        group.set_budget(Budget::person(c.clone(), Money::new(3000)));
+        group.record(Payment::new(
+            a.clone(),
+            Money::new(9000),
+            &[a.clone(), b.clone(), c.clone()],
+        ));
+        group.record(Payment::new(
+            b.clone(),
+            Money::new(4000),
+            &[a.clone(), b.clone()],
+        ));
+
+        let everyone = [a.clone(), b.clone(), c.clone()];
+        let next = Some(Money::new(6000));
+        assert_eq!(Some(c.clone()), group.who_pays_next(None));
+        assert_eq!(
+            Some(c.clone()),
+            group.payments().who_pays_next(&everyone, next)
+        );
+        assert_eq!(Some(b.clone()), group.who_pays_next(next));
+
+        // Nobody is left to pay.
+        let day = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        for person in everyone {
+            group.leave(person, day);
+        }
+        assert_eq!(None, group.who_pays_next(next));
+    }
+}
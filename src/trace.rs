@@ -0,0 +1,203 @@
+use std::fmt;
+
+use crate::{money::Money, person::Person};
+
+/// The passes the solver runs, in the order they are executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolverPass {
+    RemoveDoublyConnectedEdges,
+    SimplifyDoubleTarget,
+    RemoveSameWeightTarget,
+    RemoveZeroEdges,
+    ApplyConstraints,
+    ApplyLimits,
+    SettleThroughHub,
+    ApplyThreshold,
+}
+
+impl fmt::Display for SolverPass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RemoveDoublyConnectedEdges => write!(f, "remove doubly connected edges"),
+            Self::SimplifyDoubleTarget => write!(f, "simplify double target"),
+            Self::RemoveSameWeightTarget => write!(f, "remove same weight target"),
+            Self::RemoveZeroEdges => write!(f, "remove zero edges"),
+            Self::ApplyConstraints => write!(f, "apply constraints"),
+            Self::ApplyLimits => write!(f, "apply transfer limits"),
+            Self::SettleThroughHub => write!(f, "settle through hub"),
+            Self::ApplyThreshold => write!(f, "apply threshold"),
+        }
+    }
+}
+
+/// A single step taken by the solver while rewriting the graph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolverEvent {
+    /// A pass started running.
+    Pass(SolverPass),
+
+    /// The edges `from -> to` and `to -> from` were netted into a single `from -> to` edge.
+    /// `cancelled` is the amount both sides owed each other; an `amount` of zero means both edges were removed.
+    Merged {
+        from: Person,
+        to: Person,
+        amount: Money,
+        cancelled: Money,
+    },
+
+    /// `from` no longer pays `amount` to `via`, and pays it to `to` instead.
+    Rerouted {
+        from: Person,
+        via: Person,
+        to: Person,
+        amount: Money,
+    },
+
+    /// The edge `from -> to` went negative and was flipped into `to -> from`.
+    Reversed {
+        from: Person,
+        to: Person,
+        amount: Money,
+    },
+
+    /// The edge `from -> to` was set to zero.
+    Zeroed { from: Person, to: Person },
+
+    /// The zero edge `from -> to` was dropped from the graph.
+    Removed { from: Person, to: Person },
+}
+
+impl fmt::Display for SolverEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pass(pass) => write!(f, "pass: {pass}"),
+            Self::Merged {
+                from,
+                to,
+                amount,
+                cancelled,
+            } => write!(
+                f,
+                "{} and {} cancelled out {}, {} now pays {} {}",
+                from.raw(),
+                to.raw(),
+                cancelled.raw(),
+                from.raw(),
+                to.raw(),
+                amount.raw()
+            ),
+            Self::Rerouted {
+                from,
+                via,
+                to,
+                amount,
+            } => write!(
+                f,
+                "{} pays {} to {} instead of {}",
+                from.raw(),
+                amount.raw(),
+                to.raw(),
+                via.raw()
+            ),
+            Self::Reversed { from, to, amount } => write!(
+                f,
+                "{} no longer pays {}, {} pays {} {}",
+                from.raw(),
+                to.raw(),
+                to.raw(),
+                from.raw(),
+                amount.raw()
+            ),
+            Self::Zeroed { from, to } => write!(f, "{} owes {} nothing", from.raw(), to.raw()),
+            Self::Removed { from, to } => write!(f, "removed {} -> {}", from.raw(), to.raw()),
+        }
+    }
+}
+
+/// The ordered list of events recorded by [`crate::Solver::solve_traced`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SolverTrace(Vec<SolverEvent>);
+
+impl SolverTrace {
+    #[inline(always)]
+    pub fn new(events: &[SolverEvent]) -> Self {
+        Self(events.to_vec())
+    }
+
+    #[inline(always)]
+    pub const fn raw(&self) -> &Vec<SolverEvent> {
+        &self.0
+    }
+
+    /// Events that involve `person`, either as payer, payee or intermediary.
+    pub fn involving<'a>(&'a self, person: &'a Person) -> impl Iterator<Item = &'a SolverEvent> {
+        self.0.iter().filter(move |event| match event {
+            SolverEvent::Pass(_) => false,
+            SolverEvent::Merged { from, to, .. }
+            | SolverEvent::Reversed { from, to, .. }
+            | SolverEvent::Zeroed { from, to }
+            | SolverEvent::Removed { from, to } => from == person || to == person,
+            SolverEvent::Rerouted { from, via, to, .. } => {
+                from == person || via == person || to == person
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Payment, Payments};
+
+    #[test]
+    fn test_solve_traced() {
+        let payments = Payments::builder()
+            .record(Payment::new(
+                Person::new("A"),
+                Money::new(2000),
+                &[Person::new("B"), Person::new("C"), Person::new("H")],
+            ))
+            .record(Payment::new(
+                Person::new("C"),
+                Money::new(500),
+                &[Person::new("H")],
+            ))
+            .record(Payment::new(
+                Person::new("B"),
+                Money::new(600),
+                &[Person::new("C"), Person::new("H")],
+            ))
+            .build();
+
+        let (obligations, trace) = payments.who_pays_whom_traced().unwrap();
+
+        assert_eq!(payments.who_pays_whom().unwrap(), obligations);
+
+        let passes = trace
+            .raw()
+            .iter()
+            .filter_map(|event| match event {
+                SolverEvent::Pass(pass) => Some(*pass),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                SolverPass::RemoveDoublyConnectedEdges,
+                SolverPass::SimplifyDoubleTarget,
+                SolverPass::RemoveSameWeightTarget,
+                SolverPass::RemoveDoublyConnectedEdges,
+                SolverPass::RemoveZeroEdges,
+            ],
+            passes
+        );
+
+        // H owed both B and A, and B owes A too, so H ends up paying A instead.
+        assert!(trace.involving(&Person::new("H")).any(|event| matches!(
+            event,
+            SolverEvent::Rerouted { from, via, to, .. }
+                if from == &Person::new("H") && via == &Person::new("B") && to == &Person::new("A")
+        )));
+    }
+}
@@ -0,0 +1,121 @@
+//! JavaScript bindings, built as a `cdylib` for `wasm32-unknown-unknown` and run through `wasm-bindgen`, see the README.
+//!
+//! Amounts are plain numbers in cents, such that `100` is `$1.00`, and people are plain strings.
+
+use wasm_bindgen::prelude::*;
+
+use crate::{Money, Obligation, Obligations, Payment, Payments, Person};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_DOC: &'static str = r#"
+/** Amount in cents, such that 100 is $1.00. */
+export type Money = number;
+"#;
+
+/// The payments recorded by a group, see [`Payments`].
+#[wasm_bindgen(js_name = Payments)]
+#[derive(Default)]
+pub struct JsPayments(Payments);
+
+#[wasm_bindgen(js_class = Payments)]
+impl JsPayments {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that `from` paid `amount` cents for everyone in `to`.
+    pub fn record(&mut self, from: String, amount: i32, to: Vec<String>) {
+        let to = to.into_iter().map(Person::new).collect::<Vec<_>>();
+
+        self.0
+            .record(Payment::new(Person::new(from), Money::new(amount), &to));
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.0.raw().len()
+    }
+
+    #[wasm_bindgen(js_name = whoPaysWhom)]
+    pub fn who_pays_whom(&self) -> Result<JsObligations, JsError> {
+        self.0
+            .who_pays_whom()
+            .map(JsObligations)
+            .map_err(|err| JsError::new(&err.to_string()))
+    }
+}
+
+/// The settlement returned by `Payments.whoPaysWhom`.
+#[wasm_bindgen(js_name = Obligations)]
+pub struct JsObligations(Obligations);
+
+#[wasm_bindgen(js_class = Obligations)]
+impl JsObligations {
+    #[wasm_bindgen(getter)]
+    pub fn length(&self) -> usize {
+        self.0.raw().len()
+    }
+
+    pub fn get(&self, index: usize) -> Option<JsObligation> {
+        self.0.raw().get(index).cloned().map(JsObligation)
+    }
+
+    #[wasm_bindgen(js_name = toArray)]
+    pub fn to_array(&self) -> Vec<JsObligation> {
+        self.0.raw().iter().cloned().map(JsObligation).collect()
+    }
+}
+
+/// A single transfer: `from` pays `amount` cents to `to`.
+#[wasm_bindgen(js_name = Obligation)]
+pub struct JsObligation(Obligation);
+
+#[wasm_bindgen(js_class = Obligation)]
+impl JsObligation {
+    #[wasm_bindgen(getter)]
+    pub fn from(&self) -> String {
+        self.0.from.raw().clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn to(&self) -> String {
+        self.0.to.raw().clone()
+    }
+
+    #[wasm_bindgen(getter, unchecked_return_type = "Money")]
+    pub fn amount(&self) -> i32 {
+        self.0.amount.raw()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(target_arch = "wasm32")]
+    use wasm_bindgen_test::wasm_bindgen_test as test;
+
+    #[test]
+    fn test_who_pays_whom() {
+        let mut payments = JsPayments::new();
+        payments.record("A".into(), 3000, vec!["A".into(), "B".into(), "C".into()]);
+
+        let obligations = payments.who_pays_whom().unwrap();
+        assert_eq!(2, obligations.length());
+
+        let settled = obligations
+            .to_array()
+            .iter()
+            .map(|o| (o.from(), o.to(), o.amount()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                ("B".to_owned(), "A".to_owned(), 1000),
+                ("C".to_owned(), "A".to_owned(), 1000),
+            ],
+            settled
+        );
+        assert!(obligations.get(2).is_none());
+    }
+}
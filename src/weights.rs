@@ -0,0 +1,153 @@
+use std::num::NonZeroU32;
+
+use crate::{Payments, Person};
+
+/// How many parts of the weighted payments each person takes, set once for a group,
+/// such as their incomes when a couple splits the rent in proportion to them.
+/// Everyone takes at least one part: leave someone out of a payment's `to` instead.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Weights(Vec<(Person, NonZeroU32)>);
+
+impl Weights {
+    #[inline(always)]
+    pub fn new(weights: &[(Person, NonZeroU32)]) -> Self {
+        Self(weights.to_vec())
+    }
+
+    #[inline(always)]
+    pub const fn raw(&self) -> &Vec<(Person, NonZeroU32)> {
+        &self.0
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Sets `person`'s weight, replacing the previous one.
+    #[inline(always)]
+    pub fn set(&mut self, person: Person, weight: NonZeroU32) {
+        match self.0.iter_mut().find(|(p, _)| p == &person) {
+            Some((_, existing)) => *existing = weight,
+            None => self.0.push((person, weight)),
+        }
+    }
+
+    #[inline(always)]
+    pub fn get(&self, person: &Person) -> Option<u32> {
+        self.0
+            .iter()
+            .find(|(p, _)| p == person)
+            .map(|(_, weight)| weight.get())
+    }
+
+    /// Everyone sharing a payment made [`crate::PaymentBuilder::weighted`] without having a weight,
+    /// because of whom it is split equally instead.
+    pub fn missing(&self, payments: &Payments) -> Vec<Person> {
+        let mut missing: Vec<Person> = Vec::new();
+
+        for payment in payments.raw() {
+            if !payment.is_weighted() || !payment.weights().is_empty() {
+                continue;
+            }
+
+            for person in payment.participants() {
+                if self.get(&person).is_none() && !missing.contains(&person) {
+                    missing.push(person);
+                }
+            }
+        }
+
+        missing
+    }
+
+    /// Fills in the weights of the payments made [`crate::PaymentBuilder::weighted`], so `each_pays` splits them
+    /// in proportion. Payments with weights of their own are left alone, and so are the ones where someone
+    /// has no weight, which are split equally instead, see [`Weights::missing`].
+    pub fn resolve(&self, payments: &Payments) -> Payments {
+        let resolved = payments
+            .raw()
+            .iter()
+            .map(|payment| {
+                let weights = payment
+                    .participants()
+                    .into_iter()
+                    .map(|person| self.get(&person).map(|weight| (person, weight)))
+                    .collect::<Option<Vec<_>>>();
+
+                match weights {
+                    Some(weights) if payment.is_weighted() && payment.weights().is_empty() => {
+                        weights
+                            .into_iter()
+                            .fold(payment.to_builder(), |builder, (person, weight)| {
+                                builder.weight(person, weight)
+                            })
+                            .build()
+                    }
+                    _ => payment.clone(),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Payments::new(&resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Group, Money, Payment};
+
+    #[test]
+    fn test_rent_split_by_income() {
+        let (a, b, c) = (Person::new("A"), Person::new("B"), Person::new("C"));
+
+        let mut group = Group::new(&[a.clone(), b.clone()]);
+        let weight = |weight| NonZeroU32::new(weight).unwrap();
+        group
+            .set_weight(a.clone(), weight(6000))
+            .set_weight(b.clone(), weight(3000));
+
+        let rent = |from: &Person, to: &[Person]| {
+            Payment::builder()
+                .from(from.clone())
+                .amount(Money::new(150000))
+                .to(to)
+                .weighted()
+                .build()
+        };
+
+        // A earns twice as much as B, and pays two thirds of the rent.
+        group.record(rent(&a, &[a.clone(), b.clone()]));
+        assert_eq!(
+            vec![
+                (a.clone(), Money::new(50000)),
+                (b.clone(), Money::new(-50000))
+            ],
+            group.balances()
+        );
+
+        // C has no weight, so a payment they share is split equally, and so are unweighted ones.
+        group.record(rent(&c, &[a.clone(), b.clone(), c.clone()]));
+        group.record(Payment::new(
+            b.clone(),
+            Money::new(3000),
+            &[a.clone(), b.clone()],
+        ));
+        assert_eq!(
+            vec![
+                (a.clone(), Money::new(-1500)),
+                (b.clone(), Money::new(-98500)),
+                (c.clone(), Money::new(100000))
+            ],
+            group.balances()
+        );
+        assert_eq!(vec![c.clone()], group.missing_weights());
+
+        // Nobody takes no part of the weighted payments.
+        #[cfg(feature = "serde")]
+        assert!(serde_json::from_str::<Weights>(r#"[["A", 0]]"#).is_err());
+    }
+}
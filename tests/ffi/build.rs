@@ -0,0 +1,19 @@
+/// Generates the header from the pagat sources into `OUT_DIR`, and compiles the C harness against it.
+fn main() {
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    let pagat_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../..");
+
+    println!("cargo:rerun-if-changed={pagat_dir}/src/ffi.rs");
+    println!("cargo:rerun-if-changed={pagat_dir}/cbindgen.toml");
+    println!("cargo:rerun-if-changed=harness.c");
+
+    cbindgen::generate(pagat_dir)
+        .expect("could not generate C bindings")
+        .write_to_file(format!("{out_dir}/pagat.h"));
+
+    cc::Build::new()
+        .file("harness.c")
+        .include(&out_dir)
+        .warnings_into_errors(true)
+        .compile("pagat_ffi_harness");
+}
@@ -0,0 +1,50 @@
+/* Exercises the C API the way a C caller would; see `test_c_harness` in lib.rs. */
+#include <stdio.h>
+#include <string.h>
+
+#include "pagat.h"
+
+static int failures = 0;
+
+#define CHECK(cond)                                                         \
+    do {                                                                    \
+        if (!(cond)) {                                                      \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+            failures++;                                                     \
+        }                                                                   \
+    } while (0)
+
+int pagat_ffi_harness(void) {
+    const char *everyone[] = {"A", "B", "C"};
+    const char *invalid[] = {"B", "\xff"};
+
+    PagatPayments *payments = pagat_payments_new();
+    CHECK(payments != NULL);
+
+    CHECK(pagat_payments_record(payments, "A", 3000, everyone, 3) == PAGAT_ERROR_OK);
+    CHECK(pagat_payments_record(payments, NULL, 100, everyone, 3) == PAGAT_ERROR_NULL_POINTER);
+    CHECK(pagat_payments_record(payments, "A", 100, NULL, 1) == PAGAT_ERROR_NULL_POINTER);
+    CHECK(pagat_payments_record(payments, "A", 100, invalid, 2) == PAGAT_ERROR_INVALID_UTF8);
+
+    PagatObligations *obligations = NULL;
+    CHECK(pagat_payments_who_pays_whom(payments, &obligations) == PAGAT_ERROR_OK);
+    CHECK(pagat_obligations_len(obligations) == 2);
+
+    PagatObligation o;
+    CHECK(pagat_obligations_get(obligations, 0, &o) == PAGAT_ERROR_OK);
+    CHECK(strcmp(o.from, "B") == 0 && strcmp(o.to, "A") == 0 && o.amount == 1000);
+    CHECK(pagat_obligations_get(obligations, 1, &o) == PAGAT_ERROR_OK);
+    CHECK(strcmp(o.from, "C") == 0 && strcmp(o.to, "A") == 0 && o.amount == 1000);
+    CHECK(pagat_obligations_get(obligations, 2, &o) == PAGAT_ERROR_OUT_OF_BOUNDS);
+
+    CHECK(strcmp(pagat_error_message(PAGAT_ERROR_OUT_OF_BOUNDS), "index out of bounds") == 0);
+
+    pagat_obligations_free(obligations);
+    pagat_payments_free(payments);
+
+    /* Freeing NULL is a no-op. */
+    pagat_obligations_free(NULL);
+    pagat_payments_free(NULL);
+
+    return failures;
+}
@@ -0,0 +1,30 @@
+//! Exercises the C API of `pagat` from C, see `harness.c`.
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::c_int;
+
+    // The harness calls into the C API, which lives in the pagat rlib.
+    use pagat as _;
+
+    extern "C" {
+        /// Defined in `harness.c`, returns the number of failed checks.
+        fn pagat_ffi_harness() -> c_int;
+    }
+
+    #[test]
+    fn test_c_harness() {
+        assert_eq!(0, unsafe { pagat_ffi_harness() });
+    }
+
+    #[test]
+    fn test_header_is_up_to_date() {
+        let generated = include_str!(concat!(env!("OUT_DIR"), "/pagat.h"));
+        let committed = include_str!("../../include/pagat.h");
+
+        assert_eq!(
+            committed, generated,
+            "include/pagat.h is stale, regenerate it with cbindgen"
+        );
+    }
+}